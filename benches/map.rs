@@ -0,0 +1,135 @@
+// 和 std、hashbrown 在同样的负载下对比
+// 运行：cargo bench --bench map，只跑一部分：cargo bench --bench map -- read_heavy/u64
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const N: usize = 10_000;
+
+// 三种表的公共操作，benchmark 只通过它来访问表
+trait Map<K>: Default {
+    fn with_capacity(n: usize) -> Self;
+    fn insert(&mut self, key: K, value: u64) -> Option<u64>;
+    fn get(&self, key: &K) -> Option<&u64>;
+    fn remove(&mut self, key: &K) -> Option<u64>;
+}
+
+macro_rules! impl_map {
+    ($($map:ident)::+) => {
+        impl<K: std::hash::Hash + Eq> Map<K> for $($map)::+<K, u64> {
+            fn with_capacity(n: usize) -> Self {
+                $($map)::+::with_capacity(n)
+            }
+
+            fn insert(&mut self, key: K, value: u64) -> Option<u64> {
+                self.insert(key, value)
+            }
+
+            fn get(&self, key: &K) -> Option<&u64> {
+                self.get(key)
+            }
+
+            fn remove(&mut self, key: &K) -> Option<u64> {
+                self.remove(key)
+            }
+        }
+    };
+}
+
+impl_map!(rust_hashmap::HashMap);
+impl_map!(std::collections::HashMap);
+impl_map!(hashbrown::HashMap);
+
+// 打散过的 key，避免连续整数在某些哈希下过于规整
+fn u64_keys() -> Vec<u64> {
+    (0..N as u64)
+        .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .collect()
+}
+
+fn string_keys() -> Vec<String> {
+    u64_keys().iter().map(|k| format!("key-{k:x}")).collect()
+}
+
+// 预先分配好容量，只测插入本身
+fn insert_heavy<K: Clone, M: Map<K>>(keys: &[K]) -> M {
+    let mut map = M::with_capacity(keys.len());
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.clone(), i as u64);
+    }
+    map
+}
+
+// 每个 key 查一次命中、一次不命中（不命中的 key 来自另一半）
+fn read_heavy<K, M: Map<K>>(map: &M, hits: &[K], misses: &[K]) -> u64 {
+    let mut sum = 0;
+    for (hit, miss) in hits.iter().zip(misses) {
+        sum += map.get(hit).copied().unwrap_or(0);
+        sum += map.get(miss).copied().unwrap_or(0);
+    }
+    sum
+}
+
+// 插入、查找、删除交替进行，表的大小维持在 N / 2 左右
+fn mixed<K: Clone, M: Map<K>>(keys: &[K]) -> M {
+    let mut map = M::default();
+    let half = keys.len() / 2;
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.clone(), i as u64);
+        if i >= half {
+            black_box(map.get(&keys[i - half / 2]));
+            map.remove(&keys[i - half]);
+        }
+    }
+    map
+}
+
+// 从空表开始插入，时间主要花在反复扩容上
+fn resize_dominated<K: Clone, M: Map<K>>(keys: &[K]) -> M {
+    let mut map = M::default();
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.clone(), i as u64);
+    }
+    map
+}
+
+// 一种表在一组 key 上跑全部四种负载，结果按负载分组，组内以 表/key 类型 区分
+fn bench_map<K: Clone, M: Map<K>>(c: &mut Criterion, lib: &str, name: &str, keys: &[K]) {
+    let id = BenchmarkId::new(lib, name);
+    let (present, absent) = keys.split_at(keys.len() / 2);
+
+    c.benchmark_group("insert_heavy")
+        .bench_function(id.clone(), |b| {
+            b.iter(|| insert_heavy::<K, M>(black_box(keys)))
+        });
+
+    let map: M = insert_heavy(present);
+    c.benchmark_group("read_heavy")
+        .bench_function(id.clone(), |b| {
+            b.iter(|| read_heavy(&map, black_box(present), black_box(absent)))
+        });
+
+    c.benchmark_group("mixed")
+        .bench_function(id.clone(), |b| b.iter(|| mixed::<K, M>(black_box(keys))));
+
+    c.benchmark_group("resize_dominated")
+        .bench_function(id, |b| b.iter(|| resize_dominated::<K, M>(black_box(keys))));
+}
+
+fn bench_key<K>(c: &mut Criterion, name: &str, keys: &[K])
+where
+    K: Clone + std::hash::Hash + Eq,
+{
+    bench_map::<K, rust_hashmap::HashMap<K, u64>>(c, "rust_hashmap", name, keys);
+    bench_map::<K, std::collections::HashMap<K, u64>>(c, "std", name, keys);
+    bench_map::<K, hashbrown::HashMap<K, u64>>(c, "hashbrown", name, keys);
+}
+
+fn benches(c: &mut Criterion) {
+    bench_key(c, "u64", &u64_keys());
+    bench_key(c, "string", &string_keys());
+}
+
+criterion_group!(map, benches);
+criterion_main!(map);
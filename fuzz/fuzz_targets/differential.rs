@@ -0,0 +1,75 @@
+#![no_main]
+
+// 把输入解释成一串操作，同时作用在本 crate 的 HashMap 和 std 的 HashMap 上，
+// 每一步的返回值和最终内容都必须一致
+// 运行：cargo fuzz run -a differential（-a 打开 debug assertions，才会检查内部不变量）
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+// key 用 u8，保证频繁命中已有的 key 和同一个桶
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Insert(u8, u16),
+    Get(u8),
+    GetMut(u8, u16),
+    Remove(u8),
+    ContainsKey(u8),
+    EntryOrInsert(u8, u16),
+    EntryOrInsertWith(u8, u16),
+    EntryOrDefault(u8),
+    Retain(u8),
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut map = rust_hashmap::HashMap::new();
+    let mut model = std::collections::HashMap::new();
+
+    for op in ops {
+        match op {
+            Op::Insert(k, v) => assert_eq!(map.insert(k, v), model.insert(k, v)),
+            Op::Get(k) => assert_eq!(map.get(&k), model.get(&k)),
+            Op::GetMut(k, v) => {
+                let a = map.get_mut(&k).map(|e| std::mem::replace(e, v));
+                let b = model.get_mut(&k).map(|e| std::mem::replace(e, v));
+                assert_eq!(a, b);
+            }
+            Op::Remove(k) => assert_eq!(map.remove(&k), model.remove(&k)),
+            Op::ContainsKey(k) => assert_eq!(map.contains_key(&k), model.contains_key(&k)),
+            Op::EntryOrInsert(k, v) => {
+                let a = *map.entry(k).or_insert(v);
+                let b = *model.entry(k).or_insert(v);
+                assert_eq!(a, b);
+            }
+            Op::EntryOrInsertWith(k, v) => {
+                let a = map.entry(k).or_insert_with(|| v);
+                *a = a.wrapping_add(1);
+                let b = model.entry(k).or_insert_with(|| v);
+                *b = b.wrapping_add(1);
+                assert_eq!(*a, *b);
+            }
+            Op::EntryOrDefault(k) => {
+                assert_eq!(*map.entry(k).or_default(), *model.entry(k).or_default());
+            }
+            Op::Retain(m) => {
+                let m = m.max(1);
+                map.retain(|&k, _| k % m != 0);
+                model.retain(|&k, _| k % m != 0);
+            }
+        }
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.is_empty(), model.is_empty());
+    }
+
+    assert_eq!(map.iter().count(), model.len());
+    for (k, v) in &map {
+        assert_eq!(model.get(k), Some(v));
+    }
+    let mut items: Vec<_> = map.into_iter().collect();
+    items.sort();
+    let mut expected: Vec<_> = model.into_iter().collect();
+    expected.sort();
+    assert_eq!(items, expected);
+});
@@ -0,0 +1,28 @@
+/* src/ffi.rs 的 C 声明，使用约定见 src/ffi.rs 开头的注释 */
+#ifndef RUST_HASHMAP_H
+#define RUST_HASHMAP_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct FfiMap FfiMap;
+
+FfiMap *hashmap_new(void);
+void hashmap_free(FfiMap *map);
+size_t hashmap_len(const FfiMap *map);
+bool hashmap_insert(FfiMap *map, const uint8_t *key, size_t key_len,
+                    const uint8_t *value, size_t value_len);
+const uint8_t *hashmap_get(const FfiMap *map, const uint8_t *key,
+                           size_t key_len, size_t *value_len);
+bool hashmap_remove(FfiMap *map, const uint8_t *key, size_t key_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,286 @@
+use std::{borrow::Borrow, cell::RefCell, hash::Hash};
+
+use crate::{make_hash, INITIAL_NBUCKETS};
+
+const INITIAL_CHUNK: usize = 64;
+
+// 按块分配的 bump arena：块一旦分配就不会再扩容，所以已经分配出去的引用一直有效
+// 元素只在 arena 整体 drop 时才释放
+pub struct Arena<T> {
+    chunks: RefCell<Vec<Vec<T>>>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(INITIAL_CHUNK)
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(vec![Vec::with_capacity(n.max(1))]),
+        }
+    }
+
+    // 已分配的元素个数
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        let last = chunks.last().unwrap();
+        if last.len() == last.capacity() {
+            let capacity = 2 * last.capacity();
+            chunks.push(Vec::with_capacity(capacity));
+        }
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push(value);
+        let ptr: *mut T = chunk.last_mut().unwrap();
+        // SAFETY: push 没有超过 capacity，块的内存不会移动；块只在 arena drop 时释放，
+        // 而返回的引用借用了 &self，活不过 arena；每个元素只会被返回一次，不存在别名
+        unsafe { &mut *ptr }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 链表节点，从 arena 里分配；删除后的节点挂到 free 链表上，供后续 insert 复用
+pub struct Node<'a, K, V> {
+    entry: Option<(K, V)>,
+    next: Option<&'a mut Node<'a, K, V>>,
+}
+
+impl<K, V> Node<'_, K, V> {
+    fn key(&self) -> &K {
+        &self.entry.as_ref().unwrap().0
+    }
+}
+
+pub type MapArena<'a, K, V> = Arena<Node<'a, K, V>>;
+
+// 拉链法，但链表节点来自调用方提供的 arena，只有桶数组走全局分配器
+pub struct ArenaMap<'a, K, V> {
+    arena: &'a MapArena<'a, K, V>,
+    buckets: Vec<Option<&'a mut Node<'a, K, V>>>,
+    free: Option<&'a mut Node<'a, K, V>>,
+    items: usize,
+}
+
+impl<'a, K, V> ArenaMap<'a, K, V> {
+    pub fn new_in(arena: &'a MapArena<'a, K, V>) -> Self {
+        ArenaMap {
+            arena,
+            buckets: Vec::new(),
+            free: None,
+            items: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a, K, V> {
+        Iter {
+            buckets: self.buckets.iter(),
+            node: None,
+        }
+    }
+}
+
+impl<'a, K, V> ArenaMap<'a, K, V>
+where
+    K: Hash + Eq,
+{
+    fn bucket_idx<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        Some((make_hash(key) % self.buckets.len() as u64) as usize)
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.buckets.len() {
+            0 => INITIAL_NBUCKETS,
+            n => 2 * n,
+        };
+        let mut new_buckets: Vec<Option<&'a mut Node<'a, K, V>>> =
+            (0..target_size).map(|_| None).collect();
+        for bucket in self.buckets.iter_mut() {
+            while let Some(node) = bucket.take() {
+                *bucket = node.next.take();
+                let idx = (make_hash(node.key()) % target_size as u64) as usize;
+                node.next = new_buckets[idx].take();
+                new_buckets[idx] = Some(node);
+            }
+        }
+        self.buckets = new_buckets;
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.buckets.is_empty() || self.items > 3 * self.buckets.len() / 4 {
+            self.resize();
+        }
+        let bucket_idx = self.bucket_idx(&key)?;
+        let mut cur = self.buckets[bucket_idx].as_deref_mut();
+        while let Some(node) = cur {
+            let (ekey, evalue) = node.entry.as_mut().unwrap();
+            if *ekey == key {
+                return Some(std::mem::replace(evalue, value));
+            }
+            cur = node.next.as_deref_mut();
+        }
+
+        let node = match self.free.take() {
+            Some(node) => {
+                self.free = node.next.take();
+                node.entry = Some((key, value));
+                node
+            }
+            None => self.arena.alloc(Node {
+                entry: Some((key, value)),
+                next: None,
+            }),
+        };
+        node.next = self.buckets[bucket_idx].take();
+        self.buckets[bucket_idx] = Some(node);
+        self.items += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket_idx = self.bucket_idx(key)?;
+        let mut cur = self.buckets[bucket_idx].as_deref();
+        while let Some(node) = cur {
+            let (ekey, evalue) = node.entry.as_ref().unwrap();
+            if ekey.borrow() == key {
+                return Some(evalue);
+            }
+            cur = node.next.as_deref();
+        }
+        None
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket_idx = self.bucket_idx(key)?;
+        let mut cur = self.buckets[bucket_idx].as_deref_mut();
+        while let Some(node) = cur {
+            if node.key().borrow() == key {
+                return node.entry.as_mut().map(|(_, v)| v);
+            }
+            cur = node.next.as_deref_mut();
+        }
+        None
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket_idx = self.bucket_idx(key)?;
+        let mut link = &mut self.buckets[bucket_idx];
+        while link.as_ref().is_some_and(|node| node.key().borrow() != key) {
+            link = &mut link.as_mut().unwrap().next;
+        }
+        let node = link.take()?;
+        *link = node.next.take();
+        let (_, value) = node.entry.take().unwrap();
+        node.next = self.free.take();
+        self.free = Some(node);
+        self.items -= 1;
+        Some(value)
+    }
+}
+
+pub struct Iter<'m, 'a, K, V> {
+    buckets: std::slice::Iter<'m, Option<&'a mut Node<'a, K, V>>>,
+    node: Option<&'m Node<'a, K, V>>,
+}
+
+impl<'m, K, V> Iterator for Iter<'m, '_, K, V> {
+    type Item = (&'m K, &'m V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self.node {
+                self.node = node.next.as_deref();
+                let (k, v) = node.entry.as_ref().unwrap();
+                break Some((k, v));
+            }
+            self.node = self.buckets.next()?.as_deref();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let arena = Arena::new();
+        let mut map = ArenaMap::new_in(&arena);
+        for i in 0..100 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        assert_eq!(map.insert(5, 50), Some(5));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&5), Some(&50));
+        assert_eq!(map.get(&6), Some(&7));
+        assert_eq!(map.iter().count(), 100);
+    }
+
+    #[test]
+    fn reuse_nodes() {
+        let arena = Arena::new();
+        let mut map = ArenaMap::new_in(&arena);
+        for i in 0..10 {
+            map.insert(i.to_string(), i);
+        }
+        for i in 0..5 {
+            assert_eq!(map.remove(i.to_string().as_str()), Some(i));
+        }
+        assert_eq!(map.remove("0"), None);
+        for i in 10..15 {
+            map.insert(i.to_string(), i);
+        }
+        assert_eq!(map.len(), 10);
+        assert_eq!(arena.len(), 10);
+        assert!(!map.contains_key("3"));
+        assert_eq!(map.get("12"), Some(&12));
+    }
+}
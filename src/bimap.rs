@@ -0,0 +1,186 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::HashMap;
+
+// 左右两个方向各存一张表，两边的值都需要 Clone
+pub struct BiMap<L, R> {
+    left: HashMap<L, R>,
+    right: HashMap<R, L>,
+}
+
+// insert 时被挤掉的旧 pair
+#[derive(Debug, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    // 两边都是新值
+    Neither,
+    // left 已存在，旧的 (left, right) 被删除
+    Left(L, R),
+    // right 已存在，旧的 (left, right) 被删除
+    Right(L, R),
+    // (left, right) 这一对已经存在
+    Pair(L, R),
+    // left 和 right 分别属于两个不同的旧 pair，两个都被删除
+    Both((L, R), (L, R)),
+}
+
+impl<L, R> BiMap<L, R> {
+    pub fn new() -> Self {
+        BiMap {
+            left: HashMap::new(),
+            right: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    pub fn iter(&self) -> crate::Iter<'_, L, R> {
+        self.left.iter()
+    }
+}
+
+impl<L, R> Default for BiMap<L, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L, R> BiMap<L, R>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+{
+    // 保证一一对应：和 left 或 right 冲突的旧 pair 都会被删掉并返回
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let by_left = self.remove_by_left(&left);
+        let by_right = self.remove_by_right(&right);
+        self.left.insert(left.clone(), right.clone());
+        self.right.insert(right, left);
+        match (by_left, by_right) {
+            (None, None) => Overwritten::Neither,
+            (Some((l, r)), None) => {
+                if r == *self.left.get(&l).unwrap() {
+                    Overwritten::Pair(l, r)
+                } else {
+                    Overwritten::Left(l, r)
+                }
+            }
+            (None, Some((l, r))) => Overwritten::Right(l, r),
+            (Some(pair_l), Some(pair_r)) => Overwritten::Both(pair_l, pair_r),
+        }
+    }
+
+    // 任意一边冲突时不做修改，把参数原样返回
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)> {
+        if self.left.contains_key(&left) || self.right.contains_key(&right) {
+            return Err((left, right));
+        }
+        self.left.insert(left.clone(), right.clone());
+        self.right.insert(right, left);
+        Ok(())
+    }
+
+    pub fn get_by_left<Q>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.get(left)
+    }
+
+    pub fn get_by_right<Q>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.get(right)
+    }
+
+    pub fn contains_left<Q>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.contains_key(left)
+    }
+
+    pub fn contains_right<Q>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.contains_key(right)
+    }
+
+    pub fn remove_by_left<Q>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let right = self.left.remove(left)?;
+        let left = self.right.remove(&right).unwrap();
+        Some((left, right))
+    }
+
+    pub fn remove_by_right<Q>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let left = self.right.remove(right)?;
+        let right = self.left.remove(&left).unwrap();
+        Some((left, right))
+    }
+}
+
+impl<L, R> FromIterator<(L, R)> for BiMap<L, R>
+where
+    L: Hash + Eq + Clone,
+    R: Hash + Eq + Clone,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (L, R)>,
+    {
+        let mut map = BiMap::new();
+        for (l, r) in iter {
+            map.insert(l, r);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let mut map = BiMap::new();
+        assert_eq!(map.insert("a", 1), Overwritten::Neither);
+        assert_eq!(map.insert("b", 2), Overwritten::Neither);
+        assert_eq!(map.get_by_left(&"a"), Some(&1));
+        assert_eq!(map.get_by_right(&2), Some(&"b"));
+        assert_eq!(map.remove_by_right(&1), Some(("a", 1)));
+        assert!(!map.contains_left(&"a"));
+        assert_eq!(map.insert_no_overwrite("c", 2), Err(("c", 2)));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut map: BiMap<_, _> = [("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(map.insert("a", 1), Overwritten::Pair("a", 1));
+        assert_eq!(map.insert("a", 3), Overwritten::Left("a", 1));
+        assert_eq!(map.insert("c", 3), Overwritten::Right("a", 3));
+        assert_eq!(map.insert("b", 3), Overwritten::Both(("b", 2), ("c", 3)));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_by_right(&3), Some(&"b"));
+        assert!(!map.contains_right(&2));
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{HashMap, Iter};
+
+// 每个桶对应的过滤器位数和每个 key 置位的个数；负载因子在 3/8 到 3/4 之间，
+// 折合每个 key 11 到 22 位，假阳性率大约在 1% 以下
+const BITS_PER_BUCKET: usize = 8;
+const NHASHES: u64 = 5;
+
+// 在 HashMap 旁边维护一个 Bloom 过滤器：不存在的 key 大多数时候只算一次哈希、看几个位就能返回，
+// 不用去碰桶数组和链表。过滤器随扩容一起重建；删除不能清位，
+// 被删掉的 key 积累得比现存的 key 还多时也重建一次，把假阳性率拉回来
+pub struct BloomMap<K, V> {
+    map: HashMap<K, V>,
+    bits: Vec<u64>,
+    // 上次重建之后删掉的 key 数
+    stale: usize,
+}
+
+impl<K, V> BloomMap<K, V> {
+    pub fn new() -> Self {
+        BloomMap {
+            map: HashMap::new(),
+            bits: vec![0],
+            stale: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.map
+    }
+}
+
+impl<K, V> Default for BloomMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> BloomMap<K, V>
+where
+    K: Hash + Eq,
+{
+    // 双重哈希得到 NHASHES 个位置；先乘一个奇数打散，避免和按取模选桶用的低位相关
+    fn positions<Q>(&self, key: &Q, nbits: usize) -> impl Iterator<Item = usize>
+    where
+        Q: Hash + ?Sized,
+    {
+        let hash = self
+            .map
+            .hash_builder
+            .hash_one(key)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let step = (hash >> 32) | 1;
+        let mask = (nbits - 1) as u64;
+        (0..NHASHES).map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) & mask) as usize)
+    }
+
+    fn may_contain<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + ?Sized,
+    {
+        self.positions(key, self.bits.len() * 64)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    // 位数取 2 的幂，至少 64 位，和空表时一样
+    fn rebuild(&mut self) {
+        let nbits = (self.map.buckets.len() * BITS_PER_BUCKET)
+            .next_power_of_two()
+            .max(64);
+        let mut bits = vec![0u64; nbits / 64];
+        for key in self.map.keys() {
+            for bit in self.positions(key, nbits) {
+                bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        self.bits = bits;
+        self.stale = 0;
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.may_contain(&key) {
+            if let Some(slot) = self.map.get_mut(&key) {
+                return Some(std::mem::replace(slot, value));
+            }
+        }
+        // 位置要在 key 被移进表之前算好；插入触发了扩容的话直接重建
+        let before = self.map.buckets.len();
+        let positions: Vec<_> = self.positions(&key, self.bits.len() * 64).collect();
+        self.map.insert_unique_unchecked(key, value);
+        if self.map.buckets.len() != before {
+            self.rebuild();
+        } else {
+            for bit in positions {
+                self.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.may_contain(key) {
+            return None;
+        }
+        let value = self.map.remove(key)?;
+        self.stale += 1;
+        if self.stale > self.map.len() {
+            self.rebuild();
+        }
+        Some(value)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.may_contain(key) {
+            return None;
+        }
+        self.map.get(key)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.may_contain(key) {
+            return None;
+        }
+        self.map.get_mut(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.may_contain(key) && self.map.contains_key(key)
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for BloomMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = BloomMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_lookups() {
+        let mut map: BloomMap<_, _> = (0..10_000).map(|i| (i, i)).collect();
+        assert_eq!(map.insert(5, 50), Some(5));
+        *map.get_mut(&6).unwrap() += 1;
+        for i in 7..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        let passed = (10_000..20_000).filter(|i| map.may_contain(i)).count();
+        assert!(passed < 500, "{passed}");
+        assert!(!map.contains_key(&10_000));
+
+        // 删掉的 key 多了之后会重建，过滤器里不再留着它们
+        for i in 0..6000 {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 4000);
+        let passed = (0..5000).filter(|i| map.may_contain(i)).count();
+        assert!(passed < 100, "{passed}");
+        assert_eq!(map.get(&6000), Some(&6000));
+    }
+}
@@ -0,0 +1,130 @@
+use std::{borrow::Borrow, error::Error, fmt, hash::Hash};
+
+use crate::{HashMap, Iter};
+
+// 表满时拒绝插入，被拒绝的 key 和 value 原样还给调用方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapacityExceeded<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+impl<K, V> fmt::Display for CapacityExceeded<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("map is at its maximum number of entries")
+    }
+}
+
+impl<K, V> Error for CapacityExceeded<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+}
+
+// entry 个数有硬上限的 map，用于保存不可信输入的请求级数据，满了之后 insert 返回错误而不是继续扩容
+pub struct BoundedHashMap<K, V> {
+    map: HashMap<K, V>,
+    max_len: usize,
+}
+
+impl<K, V> BoundedHashMap<K, V> {
+    pub fn new(max_len: usize) -> Self {
+        BoundedHashMap {
+            map: HashMap::new(),
+            max_len,
+        }
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.map.len() >= self.max_len
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+}
+
+impl<K, V> BoundedHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    // 覆盖已有的 key 不占用新的名额，表满时也可以成功
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityExceeded<K, V>> {
+        if let Some(old) = self.map.get_mut(&key) {
+            return Ok(Some(std::mem::replace(old, value)));
+        }
+        if self.is_full() {
+            return Err(CapacityExceeded { key, value });
+        }
+        self.map.insert_unique_unchecked(key, value);
+        Ok(None)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get_mut(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity_limit() {
+        let mut map = BoundedHashMap::new(2);
+        assert_eq!(map.insert("a", 1), Ok(None));
+        assert_eq!(map.insert("b", 2), Ok(None));
+        assert!(map.is_full());
+        assert_eq!(
+            map.insert("c", 3),
+            Err(CapacityExceeded { key: "c", value: 3 })
+        );
+        assert_eq!(map.insert("a", 10), Ok(Some(1)));
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.insert("c", 3), Ok(None));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&10));
+        assert!(!map.contains_key("b"));
+    }
+}
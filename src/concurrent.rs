@@ -0,0 +1,356 @@
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    sync::PoisonError,
+    time::{Duration, Instant},
+};
+
+// loom 模型测试时换成 loom 的锁，见下面的 loom_tests
+#[cfg(loom)]
+use loom::sync::{Mutex, MutexGuard};
+#[cfg(not(loom))]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "async")]
+use crate::SingleFlight;
+use crate::{DefaultHashBuilder, LinkedHashMap};
+
+const DEFAULT_SHARDS: usize = 16;
+
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    accessed: Instant,
+}
+
+type Shard<K, V> = Mutex<LinkedHashMap<K, Entry<V>>>;
+
+struct Policy {
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+}
+
+impl Policy {
+    fn expired<V>(&self, entry: &Entry<V>, now: Instant) -> bool {
+        self.time_to_live
+            .is_some_and(|ttl| now.duration_since(entry.inserted) >= ttl)
+            || self
+                .time_to_idle
+                .is_some_and(|tti| now.duration_since(entry.accessed) >= tti)
+    }
+}
+
+pub struct CacheBuilder<K, V> {
+    max_capacity: Option<usize>,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+    shards: usize,
+    marker: PhantomData<fn() -> Cache<K, V>>,
+}
+
+impl<K, V> CacheBuilder<K, V> {
+    // 容量平均分到每个分片，各分片独立做 LRU 淘汰，所以总条数的上限是近似的
+    pub fn max_capacity(mut self, max_capacity: usize) -> Self {
+        self.max_capacity = Some(max_capacity);
+        self
+    }
+
+    // 从插入（或者最近一次覆盖）开始算
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.time_to_live = Some(ttl);
+        self
+    }
+
+    // 从最近一次 get 或 insert 开始算
+    pub fn time_to_idle(mut self, tti: Duration) -> Self {
+        self.time_to_idle = Some(tti);
+        self
+    }
+
+    pub fn shards(mut self, shards: usize) -> Self {
+        assert!(shards > 0, "shards must be positive");
+        self.shards = shards;
+        self
+    }
+
+    pub fn build(self) -> Cache<K, V> {
+        let per_shard = self.max_capacity.map(|max| max.div_ceil(self.shards));
+        Cache {
+            shards: (0..self.shards)
+                .map(|_| Mutex::new(LinkedHashMap::with_access_order()))
+                .collect(),
+            per_shard,
+            policy: Policy {
+                time_to_live: self.time_to_live,
+                time_to_idle: self.time_to_idle,
+            },
+            hash_builder: DefaultHashBuilder::default(),
+            #[cfg(feature = "async")]
+            flights: SingleFlight::new(),
+        }
+    }
+}
+
+// 多线程共享的缓存：按 key 的哈希分到若干个各自加锁的分片，每个分片是按访问顺序排列的 LinkedHashMap，
+// 超出容量时淘汰最久未访问的 entry；过期的 entry 在访问时惰性删除，也可以用 purge_expired 定期清理。
+// 锁只在单个分片上短暂持有、从不跨越 await，get / insert 可以直接在 tokio 任务里调用；
+// 用 Arc 包一层在任务之间共享
+pub struct Cache<K, V> {
+    shards: Box<[Shard<K, V>]>,
+    per_shard: Option<usize>,
+    policy: Policy,
+    hash_builder: DefaultHashBuilder,
+    #[cfg(feature = "async")]
+    flights: SingleFlight<K, V>,
+}
+
+impl<K, V> Cache<K, V> {
+    pub fn builder() -> CacheBuilder<K, V> {
+        CacheBuilder {
+            max_capacity: None,
+            time_to_live: None,
+            time_to_idle: None,
+            shards: DEFAULT_SHARDS,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    // 包含尚未被清理的过期 entry；各分片分别加锁，结果不是一个一致的快照
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 锁内只有完整的表操作，poison 之后继续使用是安全的
+fn lock<T>(shard: &Mutex<T>) -> MutexGuard<'_, T> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq,
+{
+    fn shard<Q>(&self, key: &Q) -> MutexGuard<'_, LinkedHashMap<K, Entry<V>>>
+    where
+        Q: Hash + ?Sized,
+    {
+        let idx = (self.hash_builder.hash_one(key) % self.shards.len() as u64) as usize;
+        lock(&self.shards[idx])
+    }
+
+    // 返回 value 的克隆，锁在返回前就已释放
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        let now = Instant::now();
+        let mut shard = self.shard(key);
+        let entry = shard.get_mut(key)?;
+        if self.policy.expired(entry, now) {
+            shard.remove(key);
+            return None;
+        }
+        entry.accessed = now;
+        Some(entry.value.clone())
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = Instant::now();
+        self.shard(key)
+            .peek(key)
+            .is_some_and(|entry| !self.policy.expired(entry, now))
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        let now = Instant::now();
+        let entry = Entry {
+            value,
+            inserted: now,
+            accessed: now,
+        };
+        let mut shard = self.shard(&key);
+        shard.insert(key, entry);
+        if let Some(max) = self.per_shard {
+            while shard.len() > max {
+                shard.pop_front();
+            }
+        }
+    }
+
+    // 已过期的旧值不返回
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = Instant::now();
+        self.shard(key)
+            .remove(key)
+            .filter(|entry| !self.policy.expired(entry, now))
+            .map(|entry| entry.value)
+    }
+
+    // 逐个分片扫描，删除所有过期 entry，返回删除的个数
+    pub fn purge_expired(&self) -> usize
+    where
+        K: Clone,
+    {
+        let now = Instant::now();
+        let mut purged = 0;
+        for shard in self.shards.iter() {
+            let mut shard = lock(shard);
+            let expired: Vec<K> = shard
+                .iter()
+                .filter(|(_, entry)| self.policy.expired(entry, now))
+                .map(|(k, _)| k.clone())
+                .collect();
+            for key in &expired {
+                shard.remove(key);
+            }
+            purged += expired.len();
+        }
+        purged
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    // 未命中时调用 loader 并把结果插入缓存；同一个 key 上并发的未命中经过 SingleFlight 合并，
+    // 只有一个调用真正执行 loader，其余的等它的结果
+    pub async fn get_or_try_insert_with<L, Fut, E>(&self, key: K, loader: L) -> Result<V, E>
+    where
+        L: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+        self.flights
+            .load(key.clone(), || async {
+                // 上一次加载可能刚刚结束，它的结果已经在缓存里了
+                if let Some(value) = self.get(&key) {
+                    return Ok(value);
+                }
+                let value = loader().await?;
+                self.insert(key, value.clone());
+                Ok(value)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expire_and_evict() {
+        let cache = Cache::builder()
+            .max_capacity(4)
+            .shards(1)
+            .time_to_idle(Duration::from_millis(50))
+            .build();
+        for i in 0..5 {
+            cache.insert(i, i);
+        }
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.get(&1), Some(1));
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(!cache.contains_key(&2));
+        assert_eq!(cache.purge_expired(), 4);
+        assert!(cache.is_empty());
+
+        let cache = Cache::builder().time_to_live(Duration::ZERO).build();
+        cache.insert("a", 1);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.remove("a"), None);
+    }
+
+    #[cfg(all(feature = "async", not(loom)))]
+    #[tokio::test]
+    async fn load_once() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let cache = Arc::new(Cache::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let cache = Arc::clone(&cache);
+            let calls = Arc::clone(&calls);
+            tasks.spawn(async move {
+                cache
+                    .get_or_try_insert_with("k", || async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                        Ok::<_, ()>(42)
+                    })
+                    .await
+            });
+        }
+        while let Some(result) = tasks.join_next().await {
+            assert_eq!(result.unwrap(), Ok(42));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get("k"), Some(42));
+    }
+}
+
+// 运行：RUSTFLAGS="--cfg loom" cargo test --release --lib concurrent::loom_tests
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::{sync::Arc, thread};
+
+    use super::*;
+
+    // 两个线程往同一个满了的分片里插入，另一边同时 get 刷新访问顺序：
+    // 任何交错下分片都不超过容量，淘汰的总是当时最久未访问的那个
+    #[test]
+    fn insert_and_evict() {
+        loom::model(|| {
+            let cache = Arc::new(Cache::builder().max_capacity(2).shards(1).build());
+            cache.insert(0, 0);
+            let handle = thread::spawn({
+                let cache = Arc::clone(&cache);
+                move || {
+                    cache.insert(1, 1);
+                    cache.get(&0)
+                }
+            });
+            cache.insert(2, 2);
+            let hit = handle.join().unwrap();
+            assert_eq!(cache.len(), 2);
+            assert!(cache.contains_key(&2));
+            assert_eq!(hit.is_some(), cache.contains_key(&0));
+        });
+    }
+}
@@ -0,0 +1,146 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::HashMap;
+
+// 计数为 0 的元素不会留在 map 里
+pub struct Counter<T> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T> Counter<T> {
+    pub fn new() -> Self {
+        Counter {
+            counts: HashMap::new(),
+        }
+    }
+
+    // 不同元素的个数
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    // 所有计数之和
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, &n)| n).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(item, &n)| (item, n))
+    }
+
+    // 按计数从大到小排序
+    pub fn most_common(&self) -> Vec<(&T, usize)> {
+        let mut items: Vec<_> = self.iter().collect();
+        items.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        items
+    }
+}
+
+impl<T> Default for Counter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Hash + Eq,
+{
+    // 返回加完之后的计数
+    pub fn add(&mut self, item: T) -> usize {
+        self.add_n(item, 1)
+    }
+
+    pub fn add_n(&mut self, item: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&item);
+        }
+        let count = self.counts.entry(item).or_insert(0);
+        *count += n;
+        *count
+    }
+
+    pub fn sub<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.sub_n(item, 1)
+    }
+
+    // 计数最小减到 0，减到 0 时删除该元素
+    pub fn sub_n<Q>(&mut self, item: &Q, n: usize) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(count) = self.counts.get_mut(item) else {
+            return 0;
+        };
+        *count = count.saturating_sub(n);
+        if *count == 0 {
+            self.counts.remove(item);
+            return 0;
+        }
+        *count
+    }
+
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+}
+
+impl<T> Extend<T> for Counter<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Counter<T>
+where
+    T: Hash + Eq,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = Counter::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let mut counter: Counter<_> = "hello world".chars().collect();
+        assert_eq!(counter.count(&'l'), 3);
+        assert_eq!(counter.count(&'z'), 0);
+        assert_eq!(counter.total(), 11);
+        assert_eq!(counter.most_common()[0], (&'l', 3));
+        assert_eq!(counter.sub_n(&'o', 5), 0);
+        assert_eq!(counter.count(&'o'), 0);
+        assert_eq!(counter.len(), 7);
+        assert_eq!(counter.add_n('o', 2), 2);
+        assert_eq!(counter.sub(&'o'), 1);
+    }
+}
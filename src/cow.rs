@@ -0,0 +1,104 @@
+use std::{borrow::Borrow, hash::Hash, ops::Deref, sync::Arc};
+
+use crate::HashMap;
+
+// 写时复制：clone 只是增加引用计数，多个线程可以共享同一张大表；
+// 第一次修改时如果还有别人持有，才把整张表复制一份，代价由写者承担
+pub struct CowMap<K, V> {
+    inner: Arc<HashMap<K, V>>,
+}
+
+impl<K, V> CowMap<K, V> {
+    pub fn new() -> Self {
+        CowMap {
+            inner: Arc::new(HashMap::new()),
+        }
+    }
+
+    // 两个 CowMap 是否还在共享同一份数据
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<K, V> CowMap<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    pub fn make_mut(&mut self) -> &mut HashMap<K, V> {
+        Arc::make_mut(&mut self.inner)
+    }
+}
+
+impl<K, V> CowMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.make_mut().insert(key, value)
+    }
+
+    // key 不存在时不会触发复制
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.inner.contains_key(key) {
+            return None;
+        }
+        self.make_mut().remove(key)
+    }
+}
+
+impl<K, V> Clone for CowMap<K, V> {
+    fn clone(&self) -> Self {
+        CowMap {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<K, V> Default for CowMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Deref for CowMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &HashMap<K, V> {
+        &self.inner
+    }
+}
+
+impl<K, V> From<HashMap<K, V>> for CowMap<K, V> {
+    fn from(map: HashMap<K, V>) -> Self {
+        CowMap {
+            inner: Arc::new(map),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_on_write() {
+        let original: CowMap<_, _> = (0..100).map(|i| (i, i)).collect::<HashMap<_, _>>().into();
+        let mut copy = original.clone();
+        assert!(copy.ptr_eq(&original));
+        assert_eq!(copy.remove(&1000), None);
+        assert!(copy.ptr_eq(&original));
+
+        copy.insert(0, 100);
+        assert!(!copy.ptr_eq(&original));
+        assert_eq!(original.get(&0), Some(&0));
+        assert_eq!(copy.get(&0), Some(&100));
+        assert_eq!(copy.len(), 100);
+    }
+}
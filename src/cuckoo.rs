@@ -0,0 +1,259 @@
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash, Hasher},
+    mem,
+};
+
+use crate::DefaultHashBuilder;
+
+// 每个桶的槽位数；桶化之后负载因子可以到 90% 以上
+const SLOTS: usize = 4;
+// 一次插入最多挤走多少个 entry，超过就扩容
+const MAX_KICKS: usize = 256;
+
+// 布谷鸟哈希：每个 key 只可能在两个桶里，查找和删除最多看 2 * SLOTS 个槽位，最坏情况也是 O(1)；
+// 插入时两个桶都满了就把其中一个 entry 挤到它的另一个桶，连锁下去，挤不动了就扩容重建。
+// 适合读多写少、能接受偶尔一次很慢的插入的场景
+pub struct CuckooMap<K, V, S = DefaultHashBuilder> {
+    slots: Vec<Option<(K, V)>>,
+    items: usize,
+    hash_builder: S,
+}
+
+impl<K, V> CuckooMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> CuckooMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        CuckooMap {
+            slots: Vec::new(),
+            items: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.slots.iter(),
+        }
+    }
+
+    fn free_slot(&self, bucket: usize) -> Option<usize> {
+        (bucket * SLOTS..(bucket + 1) * SLOTS).find(|&idx| self.slots[idx].is_none())
+    }
+}
+
+impl<K, V, S> Default for CuckooMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> CuckooMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // 两个候选桶：在同一个 hasher 上再多喂一个字节得到第二个哈希，只需要遍历一次 key
+    fn buckets<Q>(&self, key: &Q) -> (usize, usize)
+    where
+        Q: Hash + ?Sized,
+    {
+        let mask = self.slots.len() / SLOTS - 1;
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        let first = hasher.finish();
+        hasher.write_u8(0xff);
+        let second = hasher.finish();
+        (first as usize & mask, second as usize & mask)
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let (first, second) = self.buckets(key);
+        let candidates = first * SLOTS..(first + 1) * SLOTS;
+        candidates
+            .chain(second * SLOTS..(second + 1) * SLOTS)
+            .find(|&idx| matches!(&self.slots[idx], Some((k, _)) if k.borrow() == key))
+    }
+
+    // 放不下时把最后拿在手里的 entry 还回来
+    fn place(&mut self, mut entry: (K, V)) -> Result<(), (K, V)> {
+        let (first, second) = self.buckets(&entry.0);
+        if let Some(idx) = self.free_slot(first).or_else(|| self.free_slot(second)) {
+            self.slots[idx] = Some(entry);
+            return Ok(());
+        }
+        let mut bucket = first;
+        for kick in 0..MAX_KICKS {
+            let idx = bucket * SLOTS + kick % SLOTS;
+            entry = self.slots[idx].replace(entry).unwrap();
+            let (first, second) = self.buckets(&entry.0);
+            bucket = if first == bucket { second } else { first };
+            if let Some(idx) = self.free_slot(bucket) {
+                self.slots[idx] = Some(entry);
+                return Ok(());
+            }
+        }
+        Err(entry)
+    }
+
+    // 用 nbuckets 个桶重建，把现有的 entry 和 extra 都放进去；还放不下就继续翻倍
+    fn rebuild(&mut self, mut nbuckets: usize, extra: Option<(K, V)>) {
+        let mut entries: Vec<_> = mem::take(&mut self.slots).into_iter().flatten().collect();
+        entries.extend(extra);
+        'retry: loop {
+            self.slots = (0..nbuckets * SLOTS).map(|_| None).collect();
+            while let Some(entry) = entries.pop() {
+                if let Err(entry) = self.place(entry) {
+                    entries.push(entry);
+                    entries.extend(mem::take(&mut self.slots).into_iter().flatten());
+                    nbuckets *= 2;
+                    continue 'retry;
+                }
+            }
+            return;
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            let (_, old) = self.slots[idx].as_mut().unwrap();
+            return Some(mem::replace(old, value));
+        }
+        if self.slots.is_empty() {
+            self.rebuild(1, None);
+        } else if 10 * (self.items + 1) > 9 * self.slots.len() {
+            self.rebuild(2 * self.slots.len() / SLOTS, None);
+        }
+        if let Err(entry) = self.place((key, value)) {
+            self.rebuild(2 * self.slots.len() / SLOTS, Some(entry));
+        }
+        self.items += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_ref().map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_mut().map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.items -= 1;
+        self.slots[idx].take().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for CuckooMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = CuckooMap::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, Option<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().flatten().next().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a CuckooMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_displace() {
+        let mut map = CuckooMap::new();
+        for i in 0..10_000 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        assert_eq!(map.insert(5, 50), Some(5));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.len(), 10_000);
+        assert_eq!(map.iter().count(), 10_000);
+        for i in 7..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        // 负载因子不会因为频繁扩容而掉得太低
+        assert!(map.slots.len() < 4 * 10_000);
+
+        for i in 0..5000 {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 5000);
+        assert!(!map.contains_key(&10));
+        assert_eq!(map.get(&5000), Some(&5000));
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::Path,
+};
+
+use crate::{hash::fnv1a, Codec, HashMap};
+
+// 文件由固定大小的页组成：第 0 页只有 MAGIC，之后每一页是一个桶。
+// 页头：局部深度 d（u8）、哈希低 d 位的取值 pattern（u64）、entry 个数（u16），
+// 之后是 entry：key 长度（u16）、value 长度（u16）、key 的字节、value 的字节；整数都是小端序。
+// 目录不落盘，open 时根据每一页的 (d, pattern) 重建：页覆盖所有低 d 位等于 pattern 的目录项
+const PAGE_SIZE: usize = 4096;
+const PAGE_HEADER: usize = 11;
+const ENTRY_HEADER: usize = 4;
+const MAGIC: &[u8; 8] = b"RHEXT001";
+// 目录最多 2^MAX_DEPTH 项（8 MiB）；到了这个深度还放不下，说明有大量 key 的哈希低位完全相同。
+// FNV-1a 不带密钥，这样的 key 可以被故意构造出来，上限要低到目录的大小不会成为问题，32 位目标上也不会溢出
+const MAX_DEPTH: u8 = 20;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn encode<T: Codec>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.encode(&mut bytes);
+    bytes
+}
+
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if rest.len() < len {
+        return Err(invalid_data("corrupt page"));
+    }
+    let (field, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(field)
+}
+
+struct Page {
+    depth: u8,
+    pattern: u64,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    dirty: bool,
+}
+
+impl Page {
+    fn size(&self) -> usize {
+        PAGE_HEADER
+            + self
+                .entries
+                .iter()
+                .map(|(k, v)| ENTRY_HEADER + k.len() + v.len())
+                .sum::<usize>()
+    }
+
+    fn find(&self, key: &[u8]) -> Option<usize> {
+        self.entries.iter().position(|(k, _)| k == key)
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Page> {
+        let depth = bytes[0];
+        let pattern = u64::from_le_bytes(bytes[1..9].try_into().unwrap());
+        let count = u16::from_le_bytes(bytes[9..11].try_into().unwrap());
+        if depth > MAX_DEPTH || pattern >> depth != 0 {
+            return Err(invalid_data("corrupt page header"));
+        }
+        let mut rest = &bytes[PAGE_HEADER..];
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let lens = take(&mut rest, ENTRY_HEADER)?;
+            let key_len = u16::from_le_bytes([lens[0], lens[1]]) as usize;
+            let value_len = u16::from_le_bytes([lens[2], lens[3]]) as usize;
+            let key = take(&mut rest, key_len)?.to_vec();
+            let value = take(&mut rest, value_len)?.to_vec();
+            entries.push((key, value));
+        }
+        Ok(Page {
+            depth,
+            pattern,
+            entries,
+            dirty: false,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PAGE_SIZE);
+        bytes.push(self.depth);
+        bytes.extend_from_slice(&self.pattern.to_le_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        for (k, v) in &self.entries {
+            bytes.extend_from_slice(&(k.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&(v.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(k);
+            bytes.extend_from_slice(v);
+        }
+        bytes.resize(PAGE_SIZE, 0);
+        bytes
+    }
+}
+
+// 基于可扩展哈希的磁盘表：目录在内存里，每次查找最多读一页。
+// 最近用到的页缓存在一张 HashMap 里，超过 cache_pages 时随便淘汰一页（脏页先写回）。
+// 修改在 flush（或 drop）之前可能只在缓存里；没有 flush 就崩溃的话文件可能不一致，需要持久性时用 DurableMap
+pub struct DiskMap<K, V> {
+    file: File,
+    directory: Vec<u64>,
+    depth: u8,
+    pages: u64,
+    cache: HashMap<u64, Page>,
+    cache_pages: usize,
+    len: usize,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> DiskMap<K, V> {
+    pub fn open(path: impl AsRef<Path>, cache_pages: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut map = DiskMap {
+            file,
+            directory: vec![1],
+            depth: 0,
+            pages: 1,
+            cache: HashMap::new(),
+            cache_pages: cache_pages.max(1),
+            len: 0,
+            marker: PhantomData,
+        };
+
+        let file_len = map.file.metadata()?.len();
+        if file_len == 0 {
+            let mut header = MAGIC.to_vec();
+            header.resize(PAGE_SIZE, 0);
+            map.file.write_all(&header)?;
+            let empty = Page {
+                depth: 0,
+                pattern: 0,
+                entries: Vec::new(),
+                dirty: true,
+            };
+            map.write_page(1, &empty)?;
+            return Ok(map);
+        }
+
+        let mut header = [0; 8];
+        map.file.read_exact(&mut header)?;
+        if &header != MAGIC || file_len % PAGE_SIZE as u64 != 0 || file_len < 2 * PAGE_SIZE as u64 {
+            return Err(invalid_data("not a disk map file"));
+        }
+        map.pages = file_len / PAGE_SIZE as u64 - 1;
+        let mut layout = Vec::new();
+        for no in 1..=map.pages {
+            let page = map.read_page(no)?;
+            map.len += page.entries.len();
+            map.depth = map.depth.max(page.depth);
+            layout.push((no, page.depth, page.pattern));
+        }
+        map.directory = vec![0; 1 << map.depth];
+        for (no, depth, pattern) in layout {
+            for idx in (pattern as usize..map.directory.len()).step_by(1 << depth) {
+                map.directory[idx] = no;
+            }
+        }
+        if map.directory.contains(&0) {
+            return Err(invalid_data("directory has holes"));
+        }
+        Ok(map)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn read_page(&mut self, no: u64) -> io::Result<Page> {
+        let mut bytes = vec![0; PAGE_SIZE];
+        self.file.seek(SeekFrom::Start(no * PAGE_SIZE as u64))?;
+        self.file.read_exact(&mut bytes)?;
+        Page::decode(&bytes)
+    }
+
+    fn write_page(&mut self, no: u64, page: &Page) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(no * PAGE_SIZE as u64))?;
+        self.file.write_all(&page.encode())
+    }
+
+    // 把一页放进缓存，必要时先淘汰别的页；写回失败时被淘汰的页留在缓存里，
+    // 这一页也照样放进去，缓存暂时超出容量，不会丢数据
+    fn cache_page(&mut self, no: u64, page: Page) -> io::Result<()> {
+        let mut result = Ok(());
+        while self.cache.len() >= self.cache_pages {
+            let (evicted, old) = self.cache.pop().unwrap();
+            if old.dirty {
+                if let Err(e) = self.write_page(evicted, &old) {
+                    self.cache.insert(evicted, old);
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.cache.insert(no, page);
+        result
+    }
+
+    fn page(&mut self, hash: u64) -> io::Result<(u64, &mut Page)> {
+        let no = self.directory[(hash & ((1 << self.depth) - 1)) as usize];
+        if !self.cache.contains_key(&no) {
+            let page = self.read_page(no)?;
+            self.cache_page(no, page)?;
+        }
+        Ok((no, self.cache.get_mut(&no).unwrap()))
+    }
+
+    // 按哈希的第 d 位把一页拆成两页，必要时先把目录翻倍
+    fn split(&mut self, no: u64) -> io::Result<()> {
+        let page = match self.cache.remove(&no) {
+            Some(page) => page,
+            None => self.read_page(no)?,
+        };
+        if page.depth >= MAX_DEPTH {
+            self.cache_page(no, page)?;
+            return Err(io::Error::other("too many keys with colliding hashes"));
+        }
+        if page.depth == self.depth {
+            self.directory.extend_from_within(..);
+            self.depth += 1;
+        }
+        let bit = 1 << page.depth;
+        let (high, low) = page
+            .entries
+            .into_iter()
+            .partition(|(k, _)| fnv1a(k) & bit != 0);
+        let low = Page {
+            depth: page.depth + 1,
+            pattern: page.pattern,
+            entries: low,
+            dirty: true,
+        };
+        let high = Page {
+            depth: page.depth + 1,
+            pattern: page.pattern | bit,
+            entries: high,
+            dirty: true,
+        };
+        self.pages += 1;
+        let high_no = self.pages;
+        for idx in (high.pattern as usize..self.directory.len()).step_by(1 << high.depth) {
+            self.directory[idx] = high_no;
+        }
+        // 目录已经指向 high_no，两页都必须进缓存；cache_page 出错时页也已经放进去了，错误留到最后再返回
+        let low = self.cache_page(no, low);
+        let high = self.cache_page(high_no, high);
+        low.and(high)
+    }
+
+    // 把缓存里的脏页写回并落盘
+    pub fn flush(&mut self) -> io::Result<()> {
+        let dirty: Vec<u64> = self
+            .cache
+            .iter()
+            .filter(|(_, page)| page.dirty)
+            .map(|(&no, _)| no)
+            .collect();
+        for no in dirty {
+            let page = self.cache.remove(&no).unwrap();
+            let written = self.write_page(no, &page);
+            self.cache.insert(
+                no,
+                Page {
+                    dirty: written.is_err(),
+                    ..page
+                },
+            );
+            written?;
+        }
+        self.file.sync_data()
+    }
+}
+
+impl<K, V> DiskMap<K, V>
+where
+    K: Codec,
+    V: Codec,
+{
+    // 编码后的 entry 必须能放进一页
+    pub fn insert(&mut self, key: K, value: V) -> io::Result<Option<V>> {
+        let key = encode(&key);
+        let value = encode(&value);
+        if key.len() > u16::MAX as usize
+            || value.len() > u16::MAX as usize
+            || PAGE_HEADER + ENTRY_HEADER + key.len() + value.len() > PAGE_SIZE
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "entry does not fit in a page",
+            ));
+        }
+        let hash = fnv1a(&key);
+        loop {
+            let (no, page) = self.page(hash)?;
+            let old = page.find(&key).map(|idx| page.entries.swap_remove(idx).1);
+            if page.size() + ENTRY_HEADER + key.len() + value.len() <= PAGE_SIZE {
+                page.entries.push((key, value));
+                page.dirty = true;
+                if old.is_none() {
+                    self.len += 1;
+                }
+                return old.map(|old| V::decode(&old)).transpose();
+            }
+            if let Some(old) = old {
+                page.entries.push((key.clone(), old));
+            }
+            self.split(no)?;
+        }
+    }
+
+    // 查找也可能读盘、淘汰缓存，所以需要 &mut self
+    pub fn get(&mut self, key: &K) -> io::Result<Option<V>> {
+        let key = encode(key);
+        let (_, page) = self.page(fnv1a(&key))?;
+        match page.find(&key) {
+            Some(idx) => V::decode(&page.entries[idx].1).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn contains_key(&mut self, key: &K) -> io::Result<bool> {
+        let key = encode(key);
+        let (_, page) = self.page(fnv1a(&key))?;
+        Ok(page.find(&key).is_some())
+    }
+
+    // 删空的页不会合并回去
+    pub fn remove(&mut self, key: &K) -> io::Result<Option<V>> {
+        let key = encode(key);
+        let (_, page) = self.page(fnv1a(&key))?;
+        let Some(idx) = page.find(&key) else {
+            return Ok(None);
+        };
+        let (_, value) = page.entries.swap_remove(idx);
+        page.dirty = true;
+        self.len -= 1;
+        V::decode(&value).map(Some)
+    }
+}
+
+impl<K, V> Drop for DiskMap<K, V> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_reopen() {
+        let path = std::env::temp_dir().join(format!("rust-hashmap-{}.disk", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut map = DiskMap::open(&path, 4).unwrap();
+            for i in 0..5000u64 {
+                assert_eq!(map.insert(i.to_string(), i).unwrap(), None);
+            }
+            assert_eq!(map.insert("7".to_string(), 70).unwrap(), Some(7));
+            for i in 0..100u64 {
+                let expected = if i == 7 { 70 } else { i };
+                assert_eq!(map.remove(&i.to_string()).unwrap(), Some(expected));
+            }
+            assert_eq!(map.remove(&"0".to_string()).unwrap(), None);
+            assert_eq!(map.len(), 4900);
+        }
+
+        let mut map: DiskMap<String, u64> = DiskMap::open(&path, 2).unwrap();
+        assert_eq!(map.len(), 4900);
+        for i in 0..5000u64 {
+            let expected = (i >= 100).then_some(i);
+            assert_eq!(map.get(&i.to_string()).unwrap(), expected);
+        }
+        let err = map.insert("x".repeat(PAGE_SIZE), 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        drop(map);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn colliding_hashes() {
+        // 找两个哈希低 MAX_DEPTH 位相同的 key，两个 entry 放不进同一页，怎么拆分都分不开
+        let mut seen = HashMap::new();
+        let (a, b) = (0..u64::MAX)
+            .find_map(|k| {
+                let low = fnv1a(&encode(&k)) & ((1 << MAX_DEPTH) - 1);
+                seen.insert(low, k).map(|other| (other, k))
+            })
+            .unwrap();
+        let path =
+            std::env::temp_dir().join(format!("rust-hashmap-{}.collide", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut map = DiskMap::open(&path, 4).unwrap();
+        let value = vec![0u8; PAGE_SIZE / 2];
+        map.insert(a, value.clone()).unwrap();
+        let err = map.insert(b, value.clone()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert!(map.directory.len() <= 1 << MAX_DEPTH);
+        assert_eq!(map.get(&a).unwrap(), Some(value));
+        assert_eq!(map.len(), 1);
+        drop(map);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    borrow::Borrow,
+    fs::{File, OpenOptions},
+    hash::Hash,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    hash::crc32,
+    snapshot::{read_field, write_atomically, write_field},
+    Codec, HashMap, Iter,
+};
+
+// 日志里每条记录是 payload 的长度（u32）、payload 的 CRC-32（u32），然后是 payload：
+// 一个操作字节加上编码后的字段，字段格式和 save_to 一样（u32 长度 + 字节）；整数都是小端序
+const INSERT: u8 = 1;
+const REMOVE: u8 = 2;
+const RECORD_HEADER: usize = 8;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// 给 payload 加上长度和校验和
+fn frame(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let len = u32::try_from(payload.len()).map_err(|_| invalid_data("record longer than 4 GiB"))?;
+    let mut record = Vec::with_capacity(RECORD_HEADER + payload.len());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&crc32(payload).to_le_bytes());
+    record.extend_from_slice(payload);
+    Ok(record)
+}
+
+// 每次写日志之后什么时候 fsync
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPolicy {
+    // 每次修改都 fsync，返回时修改已经落盘
+    Always,
+    // 每 n 次修改 fsync 一次，崩溃时最多丢失最近的 n - 1 次修改
+    Every(usize),
+    // 交给操作系统；进程崩溃不丢数据，机器掉电可能丢
+    Never,
+}
+
+// 修改先追加到预写日志（WAL）再应用到内存里的表，open 时重放日志重建整张表；
+// 日志只增不减，用 compact 把它重写成只包含当前内容的最短形式
+pub struct DurableMap<K, V> {
+    map: HashMap<K, V>,
+    log: File,
+    path: PathBuf,
+    policy: SyncPolicy,
+    unsynced: usize,
+    buf: Vec<u8>,
+    // 日志里最后一条完整记录的结尾，追加失败时截回这里
+    end: u64,
+}
+
+impl<K, V> DurableMap<K, V> {
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    // 不管 SyncPolicy 是什么，立即把已经写入的日志落盘
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.unsynced = 0;
+        self.log.sync_data()
+    }
+}
+
+impl<K, V> DurableMap<K, V>
+where
+    K: Codec + Hash + Eq,
+    V: Codec,
+{
+    // 日志末尾不完整的记录（写到一半时崩溃）会被截掉；校验和不对的记录后面还有数据时，
+    // 说明是中间的记录坏了而不是写到一半，返回 InvalidData，不丢掉后面的记录
+    pub fn open(path: impl AsRef<Path>, policy: SyncPolicy) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut bytes = Vec::new();
+        log.read_to_end(&mut bytes)?;
+
+        let mut map = HashMap::new();
+        let mut buf = Vec::new();
+        let mut valid = 0;
+        while valid < bytes.len() {
+            let rest = &bytes[valid..];
+            if rest.len() < RECORD_HEADER {
+                break;
+            }
+            let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+            let crc = u32::from_le_bytes(rest[4..8].try_into().unwrap());
+            // 超出文件结尾的记录只可能是最后一条
+            let Some(payload) = rest[RECORD_HEADER..].get(..len) else {
+                break;
+            };
+            if crc32(payload) != crc {
+                if RECORD_HEADER + len == rest.len() {
+                    break;
+                }
+                return Err(invalid_data("corrupt log record"));
+            }
+            Self::replay(&mut map, payload, &mut buf)?;
+            valid += RECORD_HEADER + len;
+        }
+        if valid < bytes.len() {
+            log.set_len(valid as u64)?;
+        }
+
+        Ok(DurableMap {
+            map,
+            log,
+            path,
+            policy,
+            unsynced: 0,
+            buf,
+            end: valid as u64,
+        })
+    }
+
+    // 校验和已经对上的记录解析失败说明格式本身有问题，不当作写到一半
+    fn replay(map: &mut HashMap<K, V>, mut payload: &[u8], buf: &mut Vec<u8>) -> io::Result<()> {
+        let rest = &mut payload;
+        let mut op = [0];
+        let parsed = rest.read_exact(&mut op).and_then(|()| match op[0] {
+            INSERT => {
+                let key = read_field(rest, buf)?;
+                let value = read_field(rest, buf)?;
+                map.insert(key, value);
+                Ok(())
+            }
+            REMOVE => {
+                let key: K = read_field(rest, buf)?;
+                map.remove(&key);
+                Ok(())
+            }
+            _ => Err(invalid_data("unknown log record")),
+        });
+        match parsed {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(invalid_data("truncated log record"))
+            }
+            Err(e) => Err(e),
+            Ok(()) if !rest.is_empty() => Err(invalid_data("trailing bytes in log record")),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    // 一条记录用一次 write 追加，再按 SyncPolicy 决定要不要 fsync；
+    // 只写了一部分就失败时把日志截回上一条完整记录的结尾，后面追加的记录不会跟在半条记录后面
+    fn append(&mut self, payload: Vec<u8>) -> io::Result<()> {
+        let record = frame(&payload)?;
+        if let Err(e) = self.log.write_all(&record) {
+            self.log.set_len(self.end)?;
+            return Err(e);
+        }
+        self.end += record.len() as u64;
+        self.unsynced += 1;
+        let sync = match self.policy {
+            SyncPolicy::Always => true,
+            SyncPolicy::Every(n) => self.unsynced >= n,
+            SyncPolicy::Never => false,
+        };
+        if sync {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    // 写日志失败时内存里的表保持不变
+    pub fn insert(&mut self, key: K, value: V) -> io::Result<Option<V>> {
+        let mut record = vec![INSERT];
+        write_field(&mut record, &key, &mut self.buf)?;
+        write_field(&mut record, &value, &mut self.buf)?;
+        self.append(record)?;
+        Ok(self.map.insert(key, value))
+    }
+
+    // key 不存在时不写日志
+    pub fn remove<Q>(&mut self, key: &Q) -> io::Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some((key, value)) = self.map.remove_entry(key) else {
+            return Ok(None);
+        };
+        let mut record = vec![REMOVE];
+        let logged =
+            write_field(&mut record, &key, &mut self.buf).and_then(|()| self.append(record));
+        if let Err(e) = logged {
+            self.map.insert(key, value);
+            return Err(e);
+        }
+        Ok(Some(value))
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // 把日志重写成每个 key 一条 insert 记录，替换是原子的
+    pub fn compact(&mut self) -> io::Result<()> {
+        write_atomically(&self.path, |writer| {
+            let mut payload = Vec::new();
+            for (k, v) in &self.map {
+                payload.clear();
+                payload.push(INSERT);
+                write_field(&mut payload, k, &mut self.buf)?;
+                write_field(&mut payload, v, &mut self.buf)?;
+                writer.write_all(&frame(&payload)?)?;
+            }
+            Ok(())
+        })?;
+        self.log = OpenOptions::new().append(true).open(&self.path)?;
+        self.end = self.log.metadata()?.len();
+        self.unsynced = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        let path = std::env::temp_dir().join(format!("rust-hashmap-{}.wal", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut map = DurableMap::open(&path, SyncPolicy::Every(16)).unwrap();
+            for i in 0..100u32 {
+                assert_eq!(map.insert(i.to_string(), i).unwrap(), None);
+            }
+            assert_eq!(map.insert("0".to_string(), 1000).unwrap(), Some(0));
+            assert_eq!(map.remove("1").unwrap(), Some(1));
+            assert_eq!(map.remove("1").unwrap(), None);
+            map.sync().unwrap();
+        }
+
+        // 模拟写到一半崩溃：末尾多出半条记录
+        let mut bytes = std::fs::read(&path).unwrap();
+        let good = bytes.clone();
+        bytes.extend_from_slice(&[20, 0, 0, 0, 1, 2, 3, 4, INSERT, 5]);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let map: DurableMap<String, u32> = DurableMap::open(&path, SyncPolicy::Always).unwrap();
+        assert_eq!(map.len(), 99);
+        assert_eq!(map.get("0"), Some(&1000));
+        assert!(!map.contains_key("1"));
+        drop(map);
+        assert_eq!(std::fs::read(&path).unwrap(), good);
+
+        // 中间的记录坏了，不能把后面的记录当成半条截掉
+        let mut bytes = good.clone();
+        bytes[RECORD_HEADER + 1] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+        let err = DurableMap::<String, u32>::open(&path, SyncPolicy::Never)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+        std::fs::write(&path, &good).unwrap();
+
+        let mut map: DurableMap<String, u32> = DurableMap::open(&path, SyncPolicy::Always).unwrap();
+
+        map.compact().unwrap();
+        map.insert("x".to_string(), 7).unwrap();
+        drop(map);
+        let map: DurableMap<String, u32> = DurableMap::open(&path, SyncPolicy::Never).unwrap();
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get("x"), Some(&7));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    borrow::Borrow,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use crate::{timer_wheel::TimerWheel, HashMap};
+
+// 每个 entry 带一个过期时间点，过期的 entry 视为不存在，在访问时惰性删除
+pub struct ExpiringMap<K, V> {
+    map: HashMap<K, (V, Instant)>,
+    wheel: Option<TimerWheel<K>>,
+}
+
+impl<K, V> ExpiringMap<K, V> {
+    pub fn new() -> Self {
+        ExpiringMap {
+            map: HashMap::new(),
+            wheel: None,
+        }
+    }
+
+    // 使用时间轮索引过期时间，tick() 只处理刚到期的 entry，而不用扫描整张表
+    pub fn with_timer_wheel(resolution: Duration) -> Self {
+        ExpiringMap {
+            map: HashMap::new(),
+            wheel: Some(TimerWheel::new(resolution)),
+        }
+    }
+
+    // 包含尚未被清理的过期 entry
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl<K, V> Default for ExpiringMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ExpiringMap<K, V>
+where
+    K: Hash + Eq,
+{
+    // 返回被覆盖的旧值（已过期的旧值不返回）
+    pub fn insert(&mut self, key: K, value: V, ttl: Duration) -> Option<V>
+    where
+        K: Clone,
+    {
+        let now = Instant::now();
+        let deadline = now + ttl;
+        if let Some(wheel) = &mut self.wheel {
+            wheel.schedule(deadline, key.clone());
+        }
+        self.map
+            .insert(key, (value, deadline))
+            .filter(|(_, deadline)| *deadline > now)
+            .map(|(value, _)| value)
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (_, deadline) = self.map.get(key)?;
+        if *deadline <= Instant::now() {
+            self.map.remove(key);
+            return None;
+        }
+        self.map.get(key).map(|(value, _)| value)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = Instant::now();
+        self.map
+            .remove(key)
+            .filter(|(_, deadline)| *deadline > now)
+            .map(|(value, _)| value)
+    }
+
+    pub fn contains_key<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // 扫描整张表，删除所有过期 entry，返回删除的个数
+    pub fn purge_expired(&mut self) -> usize {
+        let now = Instant::now();
+        let before = self.map.len();
+        self.map.retain(|_, (_, deadline)| *deadline > now);
+        before - self.map.len()
+    }
+
+    // 没有时间轮时退化为 purge_expired
+    pub fn tick(&mut self) -> usize {
+        let Some(wheel) = &mut self.wheel else {
+            return self.purge_expired();
+        };
+        let now = Instant::now();
+        let mut evicted = 0;
+        for key in wheel.advance(now) {
+            // 时间轮里可能有被重新 insert 或 remove 过的旧记录，以 map 中的 deadline 为准
+            if matches!(self.map.get(&key), Some((_, deadline)) if *deadline <= now) {
+                self.map.remove(&key);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V> ExpiringMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    // 未命中（或已过期）时才调用 loader 并等待，成功的结果以 ttl 插入，出错时表不变；
+    // 返回 value 的克隆，和 WeightedCache::get_or_try_insert_with 一致
+    pub async fn get_or_try_insert_with<L, Fut, E>(
+        &mut self,
+        key: K,
+        ttl: Duration,
+        loader: L,
+    ) -> Result<V, E>
+    where
+        L: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value.clone());
+        }
+        let value = loader().await?;
+        self.insert(key, value.clone(), ttl);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expire() {
+        let mut map = ExpiringMap::new();
+        map.insert("foo", 42, Duration::from_secs(60));
+        map.insert("bar", 7, Duration::ZERO);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"foo"), Some(&42));
+        assert_eq!(map.get(&"bar"), None);
+        // get 时惰性删除
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.insert("foo", 43, Duration::ZERO), Some(42));
+        assert_eq!(map.insert("foo", 44, Duration::ZERO), None);
+        assert_eq!(map.remove(&"foo"), None);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn purge() {
+        let mut map = ExpiringMap::new();
+        map.insert("foo", 42, Duration::from_secs(60));
+        map.insert("bar", 7, Duration::ZERO);
+        map.insert("baz", 8, Duration::ZERO);
+        assert_eq!(map.purge_expired(), 2);
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key("foo"));
+    }
+
+    #[test]
+    fn tick() {
+        let mut map = ExpiringMap::with_timer_wheel(Duration::from_millis(1));
+        map.insert("foo", 42, Duration::from_secs(60));
+        map.insert("bar", 7, Duration::from_millis(2));
+        map.insert("baz", 8, Duration::from_millis(2));
+        map.insert("baz", 9, Duration::from_secs(60));
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(map.tick(), 1);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"baz"), Some(&9));
+    }
+}
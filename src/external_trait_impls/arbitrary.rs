@@ -0,0 +1,42 @@
+use std::hash::{BuildHasher, Hash};
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::HashMap;
+
+impl<'a, K, V, S> Arbitrary<'a> for HashMap<K, V, S>
+where
+    K: Arbitrary<'a> + Hash + Eq,
+    V: Arbitrary<'a>,
+    S: BuildHasher + Default,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_take_rest_iter()?.collect()
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::HashMap;
+
+    #[test]
+    fn arbitrary() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&bytes);
+        let map = HashMap::<u8, u16>::arbitrary(&mut u).unwrap();
+        assert!(map.iter().all(|(k, v)| map.get(k) == Some(v)));
+
+        let map = HashMap::<u8, u8>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+        assert_eq!(map.iter().count(), map.len());
+    }
+}
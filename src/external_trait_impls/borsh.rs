@@ -0,0 +1,85 @@
+use std::{
+    hash::{BuildHasher, Hash},
+    io::{Error, ErrorKind, Read, Result, Write},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::HashMap;
+
+// 预分配的上限，防止恶意输入声明一个巨大的长度
+const MAX_PREALLOC: usize = 4096;
+
+// 规范编码：u32 长度 + 按 key 升序排列的 entry，同样内容的 map 总是得到同样的字节
+impl<K, V, S> BorshSerialize for HashMap<K, V, S>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        let len = u32::try_from(entries.len()).map_err(|_| ErrorKind::InvalidData)?;
+        len.serialize(writer)?;
+        for (k, v) in entries {
+            k.serialize(writer)?;
+            v.serialize(writer)?;
+        }
+        Ok(())
+    }
+}
+
+// 只接受规范编码：key 必须严格升序，否则同一个 map 可能有多种字节表示
+impl<K, V, S> BorshDeserialize for HashMap<K, V, S>
+where
+    K: BorshDeserialize + Ord + Hash,
+    V: BorshDeserialize,
+    S: BuildHasher + Default,
+{
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        let mut entries: Vec<(K, V)> = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            let k = K::deserialize_reader(reader)?;
+            let v = V::deserialize_reader(reader)?;
+            if entries.last().is_some_and(|(prev, _)| *prev >= k) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "keys must be strictly increasing",
+                ));
+            }
+            entries.push((k, v));
+        }
+        let mut map = HashMap::with_capacity_and_hasher(len, S::default());
+        // 上面已经检查过严格升序，key 不会重复
+        for (k, v) in entries {
+            map.insert_unique_unchecked(k, v);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HashMap;
+
+    #[test]
+    fn canonical() {
+        let a: HashMap<u32, String> = (0..50).map(|i| (i, i.to_string())).collect();
+        let b: HashMap<u32, String> = (0..50).rev().map(|i| (i, i.to_string())).collect();
+        let bytes = borsh::to_vec(&a).unwrap();
+        assert_eq!(bytes, borsh::to_vec(&b).unwrap());
+
+        let back: HashMap<u32, String> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(back.len(), 50);
+        assert_eq!(back.get(&7).map(String::as_str), Some("7"));
+    }
+
+    #[test]
+    fn reject_unsorted() {
+        let bytes = borsh::to_vec(&vec![(2u8, 0u8), (1, 0)]).unwrap();
+        assert!(borsh::from_slice::<HashMap<u8, u8>>(&bytes).is_err());
+        let bytes = borsh::to_vec(&vec![(1u8, 0u8), (1, 0)]).unwrap();
+        assert!(borsh::from_slice::<HashMap<u8, u8>>(&bytes).is_err());
+    }
+}
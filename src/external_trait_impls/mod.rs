@@ -0,0 +1,12 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "borsh")]
+mod borsh;
+#[cfg(feature = "proptest")]
+pub(crate) mod proptest;
+#[cfg(feature = "rand")]
+mod rand;
+#[cfg(feature = "rayon")]
+pub(crate) mod rayon;
+#[cfg(feature = "serde")]
+mod serde;
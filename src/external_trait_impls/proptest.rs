@@ -0,0 +1,130 @@
+use std::{fmt, hash::Hash};
+
+use proptest::{
+    collection::{self, SizeRange},
+    strategy::{NewTree, Strategy, ValueTree},
+    test_runner::TestRunner,
+};
+
+use crate::HashMap;
+
+// 生成和缩小都交给 proptest 自带的 std HashMap 策略（保证 key 不重复，缩小时逐个删除 entry），
+// 每次取值时再转换成本 crate 的 HashMap
+pub fn hash_map<K, V>(key: K, value: V, size: impl Into<SizeRange>) -> HashMapStrategy<K, V>
+where
+    K: Strategy,
+    V: Strategy,
+    K::Value: Hash + Eq,
+{
+    HashMapStrategy {
+        inner: collection::hash_map(key, value, size),
+    }
+}
+
+#[must_use = "strategies do nothing unless used"]
+pub struct HashMapStrategy<K, V>
+where
+    K: Strategy,
+    V: Strategy,
+    K::Value: Hash + Eq,
+{
+    inner: collection::HashMapStrategy<K, V>,
+}
+
+impl<K, V> fmt::Debug for HashMapStrategy<K, V>
+where
+    K: Strategy,
+    V: Strategy,
+    K::Value: Hash + Eq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashMapStrategy").finish_non_exhaustive()
+    }
+}
+
+impl<K, V> Strategy for HashMapStrategy<K, V>
+where
+    K: Strategy,
+    V: Strategy,
+    K::Value: Hash + Eq,
+{
+    type Tree = HashMapValueTree<K::Tree, V::Tree>;
+    type Value = HashMap<K::Value, V::Value>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(HashMapValueTree {
+            inner: self.inner.new_tree(runner)?,
+        })
+    }
+}
+
+pub struct HashMapValueTree<K, V>
+where
+    K: ValueTree,
+    V: ValueTree,
+    K::Value: Hash + Eq,
+{
+    inner: collection::HashMapValueTree<K, V>,
+}
+
+impl<K, V> fmt::Debug for HashMapValueTree<K, V>
+where
+    K: ValueTree,
+    V: ValueTree,
+    K::Value: Hash + Eq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashMapValueTree").finish_non_exhaustive()
+    }
+}
+
+impl<K, V> ValueTree for HashMapValueTree<K, V>
+where
+    K: ValueTree,
+    V: ValueTree,
+    K::Value: Hash + Eq,
+{
+    type Value = HashMap<K::Value, V::Value>;
+
+    fn current(&self) -> Self::Value {
+        self.inner.current().into_iter().collect()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.inner.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.inner.complicate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::*, test_runner::TestRunner};
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn size_in_range(map in hash_map(any::<u16>(), any::<u8>(), 3..20)) {
+            prop_assert!((3..20).contains(&map.len()));
+            prop_assert_eq!(map.iter().count(), map.len());
+        }
+    }
+
+    #[test]
+    fn shrink_removes_entries() {
+        let mut runner = TestRunner::deterministic();
+        let mut tree = hash_map(0..1000u32, any::<u8>(), 5..10)
+            .new_tree(&mut runner)
+            .unwrap();
+        let mut len = tree.current().len();
+        while tree.simplify() {
+            let current = tree.current();
+            assert!(current.len() <= len && current.len() >= 5);
+            len = current.len();
+        }
+        assert_eq!(len, 5);
+    }
+}
@@ -0,0 +1,56 @@
+use rand::{seq::index, Rng};
+
+use crate::HashMap;
+
+// 均匀地在所有 entry 中抽样：先抽出 [0, len) 中的下标，再按桶顺序数过去
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn get_random<R>(&self, rng: &mut R) -> Option<(&K, &V)>
+    where
+        R: Rng + ?Sized,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        self.iter().nth(rng.random_range(0..self.len()))
+    }
+
+    // 不放回地抽取 min(amount, len) 个 entry，返回顺序和迭代顺序一致
+    pub fn sample_iter<R>(&self, rng: &mut R, amount: usize) -> impl Iterator<Item = (&K, &V)>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut picked = index::sample(rng, self.len(), amount.min(self.len())).into_vec();
+        picked.sort_unstable();
+        let mut picked = picked.into_iter().peekable();
+        self.iter()
+            .enumerate()
+            .filter(move |&(i, _)| picked.next_if_eq(&i).is_some())
+            .map(|(_, entry)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::HashMap;
+
+    #[test]
+    fn sample() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let empty: HashMap<i32, i32> = HashMap::new();
+        assert_eq!(empty.get_random(&mut rng), None);
+        assert_eq!(empty.sample_iter(&mut rng, 3).count(), 0);
+
+        let map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let (k, v) = map.get_random(&mut rng).unwrap();
+        assert_eq!(k, v);
+
+        let mut keys: Vec<_> = map.sample_iter(&mut rng, 10).map(|(&k, _)| k).collect();
+        assert_eq!(keys.len(), 10);
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 10);
+        assert_eq!(map.sample_iter(&mut rng, 1000).count(), 100);
+    }
+}
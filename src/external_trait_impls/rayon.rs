@@ -0,0 +1,369 @@
+use std::hash::{BuildHasher, Hash};
+
+use rayon::iter::{
+    plumbing::UnindexedConsumer, FromParallelIterator, IntoParallelIterator,
+    IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelExtend, ParallelIterator,
+};
+
+use crate::HashMap;
+
+// 并行迭代按桶切分：rayon 把桶数组拆成若干段，每段内部顺序遍历各个桶
+
+pub struct ParIter<'a, K, V> {
+    buckets: &'a [Vec<(K, V)>],
+}
+
+impl<'a, K, V> ParallelIterator for ParIter<'a, K, V>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Item = (&'a K, &'a V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.buckets
+            .par_iter()
+            .flat_map_iter(|bucket| bucket.iter().map(|(k, v)| (k, v)))
+            .drive_unindexed(consumer)
+    }
+}
+
+pub struct ParIterMut<'a, K, V> {
+    buckets: &'a mut [Vec<(K, V)>],
+}
+
+impl<'a, K, V> ParallelIterator for ParIterMut<'a, K, V>
+where
+    K: Sync + Send,
+    V: Send,
+{
+    type Item = (&'a K, &'a mut V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.buckets
+            .par_iter_mut()
+            .flat_map_iter(|bucket| bucket.iter_mut().map(|(k, v)| (&*k, v)))
+            .drive_unindexed(consumer)
+    }
+}
+
+pub struct IntoParIter<K, V> {
+    buckets: Vec<Vec<(K, V)>>,
+}
+
+impl<K, V> ParallelIterator for IntoParIter<K, V>
+where
+    K: Send,
+    V: Send,
+{
+    type Item = (K, V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.buckets
+            .into_par_iter()
+            .flat_map_iter(|bucket| bucket.into_iter())
+            .drive_unindexed(consumer)
+    }
+}
+
+pub struct ParKeys<'a, K, V> {
+    inner: ParIter<'a, K, V>,
+}
+
+impl<'a, K, V> ParallelIterator for ParKeys<'a, K, V>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Item = &'a K;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.map(|(k, _)| k).drive_unindexed(consumer)
+    }
+}
+
+pub struct ParValues<'a, K, V> {
+    inner: ParIter<'a, K, V>,
+}
+
+impl<'a, K, V> ParallelIterator for ParValues<'a, K, V>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Item = &'a V;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.map(|(_, v)| v).drive_unindexed(consumer)
+    }
+}
+
+// 只交出 &mut V，不会跨线程共享 &K，所以不要求 K: Sync
+pub struct ParValuesMut<'a, K, V> {
+    buckets: &'a mut [Vec<(K, V)>],
+}
+
+impl<'a, K, V> ParallelIterator for ParValuesMut<'a, K, V>
+where
+    K: Send,
+    V: Send,
+{
+    type Item = &'a mut V;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.buckets
+            .par_iter_mut()
+            .flat_map_iter(|bucket| bucket.iter_mut().map(|(_, v)| v))
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn par_keys(&self) -> ParKeys<'_, K, V>
+    where
+        K: Sync,
+        V: Sync,
+    {
+        ParKeys {
+            inner: self.par_iter(),
+        }
+    }
+
+    pub fn par_values(&self) -> ParValues<'_, K, V>
+    where
+        K: Sync,
+        V: Sync,
+    {
+        ParValues {
+            inner: self.par_iter(),
+        }
+    }
+
+    pub fn par_values_mut(&mut self) -> ParValuesMut<'_, K, V>
+    where
+        K: Send,
+        V: Send,
+    {
+        ParValuesMut {
+            buckets: &mut self.buckets,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoParallelIterator for &'a HashMap<K, V, S>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Item = (&'a K, &'a V);
+    type Iter = ParIter<'a, K, V>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter {
+            buckets: &self.buckets,
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoParallelIterator for &'a mut HashMap<K, V, S>
+where
+    K: Sync + Send,
+    V: Send,
+{
+    type Item = (&'a K, &'a mut V);
+    type Iter = ParIterMut<'a, K, V>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut {
+            buckets: &mut self.buckets,
+        }
+    }
+}
+
+impl<K, V, S> IntoParallelIterator for HashMap<K, V, S>
+where
+    K: Send,
+    V: Send,
+{
+    type Item = (K, V);
+    type Iter = IntoParIter<K, V>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter {
+            buckets: self.buckets,
+        }
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Send + Sync,
+    V: Send,
+{
+    // 每个桶互不相干，按桶区间分给各个线程去筛
+    pub fn par_retain<F>(&mut self, f: F)
+    where
+        F: Fn(&K, &mut V) -> bool + Sync,
+    {
+        let removed: usize = self
+            .buckets
+            .par_iter_mut()
+            .map(|bucket| {
+                let before = bucket.len();
+                bucket.retain_mut(|(k, v)| f(k, v));
+                before - bucket.len()
+            })
+            .sum();
+        self.items -= removed;
+    }
+
+    // 取走所有 entry，桶的个数保持不变
+    pub fn par_drain(&mut self) -> IntoParIter<K, V> {
+        let nbuckets = self.buckets.len();
+        let buckets = std::mem::replace(
+            &mut self.buckets,
+            (0..nbuckets).map(|_| Vec::new()).collect(),
+        );
+        self.items = 0;
+        IntoParIter { buckets }
+    }
+}
+
+// 每个线程先各自建一张表，最后再两两合并
+fn fold_maps<K, V, S, I>(par_iter: I) -> impl ParallelIterator<Item = HashMap<K, V, S>>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Default + Send,
+    I: IntoParallelIterator<Item = (K, V)>,
+{
+    par_iter
+        .into_par_iter()
+        .fold(HashMap::default, |mut map, (k, v)| {
+            map.insert(k, v);
+            map
+        })
+}
+
+impl<K, V, S> FromParallelIterator<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Default + Send,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        // 把小表并进大表；重复的 key 仍然以靠后的一侧为准
+        fold_maps(par_iter).reduce(HashMap::default, |mut left, mut right| {
+            if left.len() >= right.len() {
+                left.extend(right);
+                left
+            } else {
+                for (k, v) in left {
+                    right.get_or_insert_with(k, || v);
+                }
+                right
+            }
+        })
+    }
+}
+
+impl<K, V, S> ParallelExtend<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash + Send,
+    V: Send,
+    S: BuildHasher + Default + Send,
+{
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let maps: Vec<HashMap<K, V, S>> = fold_maps(par_iter).collect();
+        self.reserve(maps.iter().map(HashMap::len).sum());
+        for map in maps {
+            self.extend(map);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use crate::HashMap;
+
+    #[test]
+    fn par_iter() {
+        let mut map: HashMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        assert_eq!(map.par_iter().map(|(_, &v)| v).sum::<i32>(), 999 * 1000 / 2);
+        map.par_iter_mut().for_each(|(&k, v)| *v += k);
+        assert_eq!(map.get(&10), Some(&20));
+        let mut items: Vec<_> = map.into_par_iter().collect();
+        items.sort();
+        assert_eq!(items.len(), 1000);
+        assert_eq!(items[999], (999, 1998));
+    }
+
+    #[test]
+    fn par_collect() {
+        let map: HashMap<_, _> = (0..10_000).into_par_iter().map(|i| (i % 5000, i)).collect();
+        assert_eq!(map.len(), 5000);
+        assert_eq!(map.get(&42), Some(&5042));
+
+        let mut map: HashMap<_, _> = (0..10).map(|i| (i, 0)).collect();
+        map.par_extend((5..1000).into_par_iter().map(|i| (i, i)));
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&4), Some(&0));
+        assert_eq!(map.get(&5), Some(&5));
+    }
+
+    #[test]
+    fn par_retain_drain() {
+        let mut map: HashMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        map.par_retain(|&k, v| {
+            *v *= 2;
+            k % 3 == 0
+        });
+        assert_eq!(map.len(), 334);
+        assert_eq!(map.get(&3), Some(&6));
+        assert!(!map.contains_key(&4));
+
+        let sum: i32 = map.par_drain().map(|(_, v)| v).sum();
+        assert_eq!(
+            sum,
+            (0..1000).filter(|k| k % 3 == 0).map(|k| k * 2).sum::<i32>()
+        );
+        assert!(map.is_empty());
+        map.insert(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+    }
+
+    #[test]
+    fn par_keys_values() {
+        let mut map: HashMap<_, _> = (0..1000).map(|i| (i, i as f64)).collect();
+        assert_eq!(map.par_keys().sum::<i32>(), 999 * 1000 / 2);
+        map.par_values_mut().for_each(|v| *v *= 0.5);
+        assert_eq!(map.par_values().sum::<f64>(), 999.0 * 1000.0 / 4.0);
+        assert_eq!(map.get(&10), Some(&5.0));
+    }
+}
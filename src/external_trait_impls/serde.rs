@@ -0,0 +1,99 @@
+use std::{
+    fmt,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::HashMap;
+
+// 预分配的上限，防止恶意输入声明一个巨大的长度
+const MAX_PREALLOC: usize = 4096;
+
+impl<K, V, H> Serialize for HashMap<K, V, H>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (k, v) in self {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+}
+
+struct MapVisitor<K, V, H> {
+    marker: PhantomData<HashMap<K, V, H>>,
+}
+
+impl<'de, K, V, H> Visitor<'de> for MapVisitor<K, V, H>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    type Value = HashMap<K, V, H>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let capacity = access.size_hint().unwrap_or(0).min(MAX_PREALLOC);
+        let mut map = HashMap::with_capacity_and_hasher(capacity, H::default());
+        while let Some((k, v)) = access.next_entry()? {
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+
+impl<'de, K, V, H> Deserialize<'de> for HashMap<K, V, H>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor {
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HashMap;
+
+    #[test]
+    fn roundtrip() {
+        let mut map = HashMap::new();
+        map.insert("foo".to_string(), 42);
+        map.insert("bar".to_string(), 7);
+        let json = serde_json::to_string(&map).unwrap();
+        let back: HashMap<String, i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 2);
+        assert_eq!(back.get("foo"), Some(&42));
+        assert_eq!(back.get("bar"), Some(&7));
+        assert_eq!(
+            serde_json::to_string(&HashMap::<u8, u8>::new()).unwrap(),
+            "{}"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+#![allow(clippy::missing_safety_doc)]
+
+// 给 C / C++ 用的接口：key 和 value 都是字节串，插入时复制一份，表里存的是自己的拷贝。
+// 调用方的约定：
+// - map 必须来自 hashmap_new，且没有被 hashmap_free 过；同一个 map 不能被多个线程同时使用
+// - (ptr, len) 必须指向 len 个可读的字节；len 为 0 时 ptr 可以是 NULL
+// - hashmap_get 返回的指针指向表内的数据，下一次修改这个 map 之前有效
+
+use std::{ptr, slice};
+
+use crate::HashMap;
+
+pub struct FfiMap {
+    map: HashMap<Box<[u8]>, Box<[u8]>>,
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(data, len) }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn hashmap_new() -> *mut FfiMap {
+    Box::into_raw(Box::new(FfiMap {
+        map: HashMap::new(),
+    }))
+}
+
+// map 为 NULL 时什么也不做
+#[no_mangle]
+pub unsafe extern "C" fn hashmap_free(map: *mut FfiMap) {
+    if !map.is_null() {
+        drop(unsafe { Box::from_raw(map) });
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn hashmap_len(map: *const FfiMap) -> usize {
+    unsafe { &*map }.map.len()
+}
+
+// 覆盖了已有的 value 时返回 true
+#[no_mangle]
+pub unsafe extern "C" fn hashmap_insert(
+    map: *mut FfiMap,
+    key: *const u8,
+    key_len: usize,
+    value: *const u8,
+    value_len: usize,
+) -> bool {
+    let map = unsafe { &mut *map };
+    let key = unsafe { bytes(key, key_len) };
+    let value = unsafe { bytes(value, value_len) };
+    map.map.insert(key.into(), value.into()).is_some()
+}
+
+// 找不到时返回 NULL；找到时把 value 的长度写到 value_len（可以为 NULL）
+#[no_mangle]
+pub unsafe extern "C" fn hashmap_get(
+    map: *const FfiMap,
+    key: *const u8,
+    key_len: usize,
+    value_len: *mut usize,
+) -> *const u8 {
+    let map = unsafe { &*map };
+    let key = unsafe { bytes(key, key_len) };
+    match map.map.get(key) {
+        Some(value) => {
+            if !value_len.is_null() {
+                unsafe { *value_len = value.len() };
+            }
+            value.as_ptr()
+        }
+        None => ptr::null(),
+    }
+}
+
+// 删除成功时返回 true
+#[no_mangle]
+pub unsafe extern "C" fn hashmap_remove(map: *mut FfiMap, key: *const u8, key_len: usize) -> bool {
+    let map = unsafe { &mut *map };
+    let key = unsafe { bytes(key, key_len) };
+    map.map.remove(key).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        unsafe {
+            let map = hashmap_new();
+            let (key, value) = (b"foo", b"bar!");
+            assert!(!hashmap_insert(map, key.as_ptr(), 3, value.as_ptr(), 4));
+            assert!(hashmap_insert(map, key.as_ptr(), 3, value.as_ptr(), 3));
+            assert!(!hashmap_insert(map, ptr::null(), 0, ptr::null(), 0));
+            assert_eq!(hashmap_len(map), 2);
+
+            let mut len = 0;
+            let found = hashmap_get(map, key.as_ptr(), 3, &mut len);
+            assert_eq!(bytes(found, len), b"bar");
+            assert!(!hashmap_get(map, ptr::null(), 0, ptr::null_mut()).is_null());
+            assert!(hashmap_get(map, b"baz".as_ptr(), 3, &mut len).is_null());
+
+            assert!(hashmap_remove(map, key.as_ptr(), 3));
+            assert!(!hashmap_remove(map, key.as_ptr(), 3));
+            assert_eq!(hashmap_len(map), 1);
+            hashmap_free(map);
+        }
+    }
+}
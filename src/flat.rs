@@ -0,0 +1,277 @@
+use std::{
+    hash::{BuildHasher, Hash},
+    mem::{self, MaybeUninit},
+};
+
+use crate::DefaultHashBuilder;
+
+// 最小的槽位数，必须是 2 的幂
+const MIN_SLOTS: usize = 8;
+
+// 面向 u32 / u64 这类小的 Copy key 的线性探测表：占用标记、key、value 各是一个平铺的数组，
+// 没有 Vec<(K, V)> 桶或者 Option 包装带来的额外指针和填充，探测时只扫 ctrl 和 keys 两段连续内存。
+// 删除用向后移位（backward shift）而不是墓碑，所以删得再多探测链也不会变长
+pub struct FlatMap<K, V, S = DefaultHashBuilder> {
+    ctrl: Vec<bool>,
+    keys: Vec<MaybeUninit<K>>,
+    values: Vec<MaybeUninit<V>>,
+    items: usize,
+    hash_builder: S,
+}
+
+impl<K, V> FlatMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> FlatMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        FlatMap {
+            ctrl: Vec::new(),
+            keys: Vec::new(),
+            values: Vec::new(),
+            items: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        Iter { map: self, idx: 0 }
+    }
+
+    fn mask(&self) -> usize {
+        self.ctrl.len() - 1
+    }
+
+    // SAFETY（对下面两个函数）：调用方保证 ctrl[idx] 为 true，此时 keys[idx] 和 values[idx] 都已初始化
+    unsafe fn key_at(&self, idx: usize) -> &K {
+        self.keys[idx].assume_init_ref()
+    }
+
+    unsafe fn value_at(&self, idx: usize) -> &V {
+        self.values[idx].assume_init_ref()
+    }
+}
+
+impl<K, V, S> Default for FlatMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> Drop for FlatMap<K, V, S> {
+    fn drop(&mut self) {
+        if !mem::needs_drop::<V>() {
+            return;
+        }
+        for (idx, &used) in self.ctrl.iter().enumerate() {
+            if used {
+                // SAFETY: ctrl[idx] 为 true 说明 values[idx] 已初始化，而且之后不会再被读
+                unsafe { self.values[idx].assume_init_drop() };
+            }
+        }
+    }
+}
+
+impl<K, V, S> FlatMap<K, V, S>
+where
+    K: Copy + Hash + Eq,
+    S: BuildHasher,
+{
+    fn home(&self, key: &K) -> usize {
+        self.hash_builder.hash_one(key) as usize & self.mask()
+    }
+
+    // 找到时返回 Ok(槽位)，否则返回 Err(探测链末尾的空槽位)；表不能是空的
+    fn probe(&self, key: &K) -> Result<usize, usize> {
+        let mut idx = self.home(key);
+        while self.ctrl[idx] {
+            // SAFETY: ctrl[idx] 为 true
+            if unsafe { self.key_at(idx) } == key {
+                return Ok(idx);
+            }
+            idx = (idx + 1) & self.mask();
+        }
+        Err(idx)
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.ctrl.is_empty() {
+            return None;
+        }
+        self.probe(key).ok()
+    }
+
+    fn resize(&mut self) {
+        let nslots = (2 * self.ctrl.len()).max(MIN_SLOTS);
+        let ctrl = mem::replace(&mut self.ctrl, vec![false; nslots]);
+        let keys = mem::replace(
+            &mut self.keys,
+            (0..nslots).map(|_| MaybeUninit::uninit()).collect(),
+        );
+        let values = mem::replace(
+            &mut self.values,
+            (0..nslots).map(|_| MaybeUninit::uninit()).collect(),
+        );
+        // 旧数组里的 value 按位搬到新表，旧的 MaybeUninit 数组 drop 时不会再释放它们
+        for ((used, key), value) in ctrl.into_iter().zip(keys).zip(values) {
+            if used {
+                // SAFETY: used 为 true 说明 key 和 value 都已初始化
+                let (key, value) = unsafe { (key.assume_init(), value.assume_init()) };
+                let Err(idx) = self.probe(&key) else {
+                    unreachable!("旧表里不会有重复的 key")
+                };
+                self.ctrl[idx] = true;
+                self.keys[idx].write(key);
+                self.values[idx].write(value);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            // SAFETY: find 返回的槽位已被占用
+            let old = unsafe { self.values[idx].assume_init_mut() };
+            return Some(mem::replace(old, value));
+        }
+        if 4 * (self.items + 1) > 3 * self.ctrl.len() {
+            self.resize();
+        }
+        let Err(idx) = self.probe(&key) else {
+            unreachable!("上面已经确认 key 不存在")
+        };
+        self.ctrl[idx] = true;
+        self.keys[idx].write(key);
+        self.values[idx].write(value);
+        self.items += 1;
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let idx = self.find(key)?;
+        // SAFETY: find 返回的槽位已被占用
+        Some(unsafe { self.value_at(idx) })
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let idx = self.find(key)?;
+        // SAFETY: find 返回的槽位已被占用
+        Some(unsafe { self.values[idx].assume_init_mut() })
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let mut hole = self.find(key)?;
+        // SAFETY: find 返回的槽位已被占用，读出之后马上把它标记为空
+        let value = unsafe { self.values[hole].assume_init_read() };
+        self.ctrl[hole] = false;
+        self.items -= 1;
+
+        // 向后移位：把后面探测链上的 entry 往前挪，直到遇到空位或者已经在 home 上的 entry
+        let mask = self.mask();
+        let mut idx = (hole + 1) & mask;
+        while self.ctrl[idx] {
+            // SAFETY: ctrl[idx] 为 true
+            let home = self.home(unsafe { self.key_at(idx) });
+            // home 不在 (hole, idx] 之间时，挪到 hole 上仍然能被探测到
+            if (idx.wrapping_sub(home) & mask) >= (idx.wrapping_sub(hole) & mask) {
+                self.ctrl.swap(hole, idx);
+                self.keys.swap(hole, idx);
+                self.values.swap(hole, idx);
+                hole = idx;
+            }
+            idx = (idx + 1) & mask;
+        }
+        Some(value)
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for FlatMap<K, V, S>
+where
+    K: Copy + Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = FlatMap::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Iter<'a, K, V, S> {
+    map: &'a FlatMap<K, V, S>,
+    idx: usize,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.idx < self.map.ctrl.len() {
+            let idx = self.idx;
+            self.idx += 1;
+            if self.map.ctrl[idx] {
+                // SAFETY: ctrl[idx] 为 true
+                return Some(unsafe { (self.map.key_at(idx), self.map.value_at(idx)) });
+            }
+        }
+        None
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FlatMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_and_shift() {
+        let mut map = FlatMap::new();
+        for i in 0..10_000u64 {
+            assert_eq!(map.insert(i, i.to_string()), None);
+        }
+        assert_eq!(map.insert(5, "x".to_string()), Some("5".to_string()));
+        map.get_mut(&6).unwrap().push('!');
+        assert_eq!(map.len(), 10_000);
+        assert_eq!(map.iter().count(), 10_000);
+        assert_eq!(map.get(&6).map(String::as_str), Some("6!"));
+
+        for i in (0..10_000).step_by(2) {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 5000);
+        // 向后移位之后剩下的 key 都还能找到
+        for i in (1..10_000).step_by(2) {
+            assert!(map.contains_key(&i), "{i}");
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{make_hash, HashMap};
+
+// 只读的紧凑布局：所有 entry 按桶顺序连续存放，offsets[i]..offsets[i + 1] 是第 i 个桶的范围
+// 桶的个数是 2 的幂且不小于元素个数，查找时只需要一次取模（位与）和一段连续的扫描
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct FrozenMap<K, V> {
+    entries: Vec<(K, V)>,
+    offsets: Vec<usize>,
+    // 构建时的 hash_tag()，归档之后用来判断读的一方算出来的哈希还对不对得上
+    #[cfg(feature = "rkyv")]
+    hash_tag: u64,
+}
+
+// make_hash 用的 DefaultHasher 不保证跨 Rust 版本稳定，usize 等类型的 Hash 在 32 位和 64 位目标上也不一样；
+// 对一个覆盖常见类型的固定值算一次哈希，结果变了就说明桶的划分已经对不上
+#[cfg(feature = "rkyv")]
+fn hash_tag() -> u64 {
+    static TAG: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+    *TAG.get_or_init(|| make_hash(&("rust-hashmap", 0x0123_4567_89ab_cdef_u64, 1usize, 'c')))
+}
+
+impl<K, V> FrozenMap<K, V> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    fn bucket_of(&self, hash: u64) -> usize {
+        (hash & (self.offsets.len() as u64 - 2)) as usize
+    }
+}
+
+impl<K, V> FrozenMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_entries(entries: impl Iterator<Item = (K, V)>) -> Self {
+        let mut entries: Vec<_> = entries.map(|(k, v)| (make_hash(&k), k, v)).collect();
+        let nbuckets = entries.len().next_power_of_two();
+        let mask = nbuckets as u64 - 1;
+        entries.sort_by_key(|(hash, _, _)| hash & mask);
+
+        let mut offsets = vec![0; nbuckets + 1];
+        for (hash, _, _) in &entries {
+            offsets[(hash & mask) as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        FrozenMap {
+            entries: entries.into_iter().map(|(_, k, v)| (k, v)).collect(),
+            offsets,
+            #[cfg(feature = "rkyv")]
+            hash_tag: hash_tag(),
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.bucket_of(make_hash(key));
+        self.entries[self.offsets[bucket]..self.offsets[bucket + 1]]
+            .iter()
+            .find(|(ekey, _)| ekey.borrow() == key)
+            .map(|(_, evalue)| evalue)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // 拷贝出一个可以修改的 HashMap；快照本身不受影响，其他持有 Arc 的线程照常读
+    pub fn thaw(&self) -> HashMap<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut map = HashMap::with_capacity(self.len());
+        for (k, v) in &self.entries {
+            map.insert_unique_unchecked(k.clone(), v.clone());
+        }
+        map
+    }
+}
+
+impl<K, V, S> From<HashMap<K, V, S>> for FrozenMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from(map: HashMap<K, V, S>) -> Self {
+        Self::from_entries(map.into_iter())
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for FrozenMap<K, V>
+where
+    K: Eq + Hash,
+{
+    // 重复的 key 以最后一次出现为准
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        FrozenMap::from(iter.into_iter().collect::<HashMap<K, V>>())
+    }
+}
+
+// 归档后的布局和 FrozenMap 一样是 offsets + 连续的 entries，可以直接在 mmap 出来的字节上查找，
+// 不需要反序列化。桶按 make_hash 划分，换了 Rust 版本或者目标平台之后哈希可能对不上：
+// 这时 get 退化成线性扫描，结果仍然正确，可以用 hash_compatible 检查之后重新生成归档
+#[cfg(feature = "rkyv")]
+impl<K, V> ArchivedFrozenMap<K, V>
+where
+    K: rkyv::Archive,
+    V: rkyv::Archive,
+{
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 读的一方和写的一方算出来的哈希是否一致
+    pub fn hash_compatible(&self) -> bool {
+        self.hash_tag.to_native() == hash_tag()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V::Archived>
+    where
+        K::Archived: PartialEq<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.hash_compatible() {
+            return self
+                .entries
+                .iter()
+                .find(|entry| entry.0 == *key)
+                .map(|entry| &entry.1);
+        }
+        let mask = self.offsets.len() as u64 - 2;
+        let bucket = (make_hash(key) & mask) as usize;
+        let start = self.offsets[bucket].to_native() as usize;
+        let end = self.offsets[bucket + 1].to_native() as usize;
+        self.entries[start..end]
+            .iter()
+            .find(|entry| entry.0 == *key)
+            .map(|entry| &entry.1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K::Archived, &V::Archived)> {
+        self.entries.iter().map(|entry| (&entry.0, &entry.1))
+    }
+}
+
+pub struct FrozenMapBuilder<K, V> {
+    map: HashMap<K, V>,
+}
+
+impl<K, V> FrozenMapBuilder<K, V> {
+    pub fn new() -> Self {
+        FrozenMapBuilder {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V> Default for FrozenMapBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> FrozenMapBuilder<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn insert(&mut self, key: K, value: V) -> &mut Self {
+        self.map.insert(key, value);
+        self
+    }
+
+    pub fn build(self) -> FrozenMap<K, V> {
+        FrozenMap::from(self.map)
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a FrozenMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freeze() {
+        let mut map = HashMap::new();
+        for i in 0..100 {
+            map.insert(i, i * 2);
+        }
+        let frozen = map.freeze();
+        assert_eq!(frozen.len(), 100);
+        for i in 0..100 {
+            assert_eq!(frozen.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(frozen.get(&100), None);
+        assert_eq!(frozen.iter().count(), 100);
+
+        let shared = std::sync::Arc::clone(&frozen);
+        std::thread::spawn(move || assert_eq!(shared.get(&7), Some(&14)))
+            .join()
+            .unwrap();
+
+        let mut thawed = frozen.thaw();
+        thawed.insert(100, 200);
+        assert_eq!(thawed.len(), 101);
+        assert_eq!(thawed.get(&99), Some(&198));
+        assert_eq!(frozen.get(&100), None);
+    }
+
+    #[test]
+    fn builder() {
+        let mut builder = FrozenMapBuilder::new();
+        builder.insert("foo", 1).insert("bar", 2).insert("foo", 3);
+        let frozen = builder.build();
+        assert_eq!(frozen.len(), 2);
+        assert_eq!(frozen.get("foo"), Some(&3));
+        assert!(frozen.contains_key("bar"));
+
+        let empty: FrozenMap<&str, i32> = FrozenMapBuilder::new().build();
+        assert!(empty.is_empty());
+        assert_eq!(empty.get("foo"), None);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn archived() {
+        use rkyv::rancor::Error;
+
+        let frozen: FrozenMap<String, u32> = (0..100).map(|i| (i.to_string(), i)).collect();
+        let bytes = rkyv::to_bytes::<Error>(&frozen).unwrap();
+        let archived = rkyv::access::<ArchivedFrozenMap<String, u32>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 100);
+        for i in 0..100 {
+            assert_eq!(archived.get(i.to_string().as_str()), Some(&i.into()));
+        }
+        assert_eq!(archived.get("100"), None);
+        assert_eq!(archived.iter().count(), 100);
+        assert!(archived.hash_compatible());
+
+        // 模拟由哈希算法不同的工具链写出的归档：桶对不上，但查找结果仍然正确
+        let mut frozen = frozen;
+        frozen.hash_tag ^= 1;
+        let bytes = rkyv::to_bytes::<Error>(&frozen).unwrap();
+        let archived = rkyv::access::<ArchivedFrozenMap<String, u32>, Error>(&bytes).unwrap();
+        assert!(!archived.hash_compatible());
+        for i in 0..100 {
+            assert_eq!(archived.get(i.to_string().as_str()), Some(&i.into()));
+        }
+        assert_eq!(archived.get("100"), None);
+    }
+}
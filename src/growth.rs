@@ -0,0 +1,55 @@
+use std::panic::RefUnwindSafe;
+
+// 负载因子超过 3/4 时，HashMap 调用 grow 决定新的桶数；返回值不大于当前桶数时不扩容，
+// 之后的 entry 继续挂在现有的链上。reserve（以及 extend、insert_batch、merge 这些按长度预分配的操作）
+// 也按策略一步步增长；只有 with_capacity 按请求的容量直接分配。
+// 要求 Send + Sync + RefUnwindSafe，这样 HashMap 原有的这些自动 trait 不受影响
+pub trait GrowthPolicy: Send + Sync + RefUnwindSafe {
+    fn grow(&self, buckets: usize) -> usize;
+}
+
+// 默认策略：桶数翻倍，摊还下来每个 entry 只搬动常数次
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn grow(&self, buckets: usize) -> usize {
+        2 * buckets
+    }
+}
+
+// 每次增长一半：扩容次数多一些，但每次新分配的桶数组和翻倍后闲置的空间都更小
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OneAndAHalf;
+
+impl GrowthPolicy for OneAndAHalf {
+    fn grow(&self, buckets: usize) -> usize {
+        buckets + buckets.div_ceil(2)
+    }
+}
+
+// 每次固定增加这么多个桶，每次扩容分配的大小可预测；表很大时扩容会很频繁
+#[derive(Debug, Clone, Copy)]
+pub struct FixedIncrement(pub usize);
+
+impl GrowthPolicy for FixedIncrement {
+    fn grow(&self, buckets: usize) -> usize {
+        buckets + self.0.max(1)
+    }
+}
+
+// 按 inner 增长，但桶数不超过 max_buckets，用来给桶数组的大小设上限；到达上限后链会越来越长
+#[derive(Debug, Clone, Copy)]
+pub struct Capped<P> {
+    pub inner: P,
+    pub max_buckets: usize,
+}
+
+impl<P> GrowthPolicy for Capped<P>
+where
+    P: GrowthPolicy,
+{
+    fn grow(&self, buckets: usize) -> usize {
+        self.inner.grow(buckets).min(self.max_buckets)
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hash, Hasher},
+    ops::Deref,
+};
+
+use crate::{make_hash, HashMap};
+
+// 固定种子的 SipHash：同样的种子、同样的插入顺序，在不同进程里得到同样的桶布局和迭代顺序，
+// 用于回放和 golden file 测试；种子公开意味着不再防 HashDoS，reseed() 也不会换种子
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixedState {
+    seed: u64,
+}
+
+impl FixedState {
+    pub const fn with_seed(seed: u64) -> Self {
+        FixedState { seed }
+    }
+}
+
+impl BuildHasher for FixedState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
+// 跨进程、跨 Rust 版本、跨平台都稳定的哈希：FNV-1a 加一个 64 位的混合函数收尾。
+// 整数一律按小端序写入，usize / isize 按 64 位写，32 位和 64 位目标上结果相同；
+// 只要 key 的 Hash 实现写出的字节不变，哈希值就不变。不防 HashDoS
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher {
+    hash: u64,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher {
+            hash: 0xcbf2_9ce4_8422_2325,
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    // FNV-1a 高位分布不好，用 MurmurHash3 的 fmix64 打散
+    fn finish(&self) -> u64 {
+        let mut h = self.hash;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        h ^ (h >> 33)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.hash = (self.hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.write_u64(n as i64 as u64);
+    }
+}
+
+pub type StableState = BuildHasherDefault<StableHasher>;
+
+// 不做哈希，整数 key 本身就是哈希值；适合已经分布均匀的 ID（例如 ECS 的实体 ID）。
+// 桶数是 2 的幂，取模只看低位，所以低位总是相同的 key（比如都是 1024 的倍数）会全部挤在一个桶里
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdentityHasher {
+    hash: u64,
+}
+
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    // 非整数的 key（字符串、元组、结构体）也能用：每次写入都先循环左移再异或进去，
+    // 所有字段都参与哈希，但分布没有任何保证。只写一个整数时状态从 0 开始，结果就是这个整数本身
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.fold(byte.into());
+        }
+    }
+
+    fn write_u8(&mut self, n: u8) {
+        self.fold(n.into());
+    }
+
+    fn write_u16(&mut self, n: u16) {
+        self.fold(n.into());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.fold(n.into());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.fold(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.fold(n as u64);
+    }
+
+    fn write_i8(&mut self, n: i8) {
+        self.write_u8(n as u8);
+    }
+
+    fn write_i16(&mut self, n: i16) {
+        self.write_u16(n as u16);
+    }
+
+    fn write_i32(&mut self, n: i32) {
+        self.write_u32(n as u32);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.write_usize(n as usize);
+    }
+}
+
+impl IdentityHasher {
+    fn fold(&mut self, n: u64) {
+        self.hash = self.hash.rotate_left(8) ^ n;
+    }
+}
+
+pub type BuildIdentityHasher = BuildHasherDefault<IdentityHasher>;
+
+pub type IntMap<K, V> = HashMap<K, V, BuildIdentityHasher>;
+
+// 把哈希值和 key 存在一起，创建时算一次，之后每次查找都直接用它；
+// 配合 IdentityHasher（PreHashedMap）使用时，表里不会再对 key 做任何哈希
+#[derive(Debug, Clone, Copy)]
+pub struct PreHashed<K> {
+    hash: u64,
+    key: K,
+}
+
+impl<K: Hash> PreHashed<K> {
+    // 用固定种子的 SipHash，同一个 key 在任何地方得到的都是同一个 PreHashed
+    pub fn new(key: K) -> Self {
+        PreHashed {
+            hash: make_hash(&key),
+            key,
+        }
+    }
+
+    pub fn with_hasher<S: BuildHasher>(key: K, hash_builder: &S) -> Self {
+        PreHashed {
+            hash: hash_builder.hash_one(&key),
+            key,
+        }
+    }
+}
+
+impl<K> PreHashed<K> {
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn into_inner(self) -> K {
+        self.key
+    }
+}
+
+impl<K> Deref for PreHashed<K> {
+    type Target = K;
+
+    fn deref(&self) -> &K {
+        &self.key
+    }
+}
+
+impl<K> Hash for PreHashed<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+// 先比哈希值，不同的话不用再比 key
+impl<K: PartialEq> PartialEq for PreHashed<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for PreHashed<K> {}
+
+pub type PreHashedMap<K, V> = HashMap<PreHashed<K>, V, BuildIdentityHasher>;
+
+// 更快但不是密码学强度的哈希，需要打开对应的 feature
+#[cfg(feature = "ahash")]
+pub type AHashMap<K, V> = HashMap<K, V, ahash::RandomState>;
+
+#[cfg(feature = "rustc-hash")]
+pub type FxHashMap<K, V> = HashMap<K, V, rustc_hash::FxBuildHasher>;
+
+// 落盘格式用的哈希：FNV-1a，结果和进程、Rust 版本都无关
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// CRC-32（IEEE，和 zlib 一样），给落盘的记录做校验；逐位计算，不需要查找表
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashMap;
+
+    #[test]
+    fn reproducible() {
+        assert_eq!(
+            FixedState::with_seed(7).hash_one("foo"),
+            FixedState::with_seed(7).hash_one("foo")
+        );
+        assert_ne!(
+            FixedState::with_seed(7).hash_one("foo"),
+            FixedState::with_seed(8).hash_one("foo")
+        );
+
+        // randomize-iteration 会按桶数组的地址打乱起点，迭代顺序就不再可复现了
+        if cfg!(all(feature = "randomize-iteration", debug_assertions)) {
+            return;
+        }
+        let build = || {
+            let mut map = HashMap::with_hasher(FixedState::with_seed(7));
+            map.extend((0..100).map(|i| (i, i)));
+            map
+        };
+        let a: Vec<_> = build().into_iter().collect();
+        let b: Vec<_> = build().into_iter().collect();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn stable() {
+        // 固定的期望值：换 Rust 版本、换平台都不能变
+        assert_eq!(StableState::default().hash_one(0u64), 0x7bd3_144f_29c0_cc9e);
+        assert_eq!(
+            StableState::default().hash_one(7usize),
+            StableState::default().hash_one(7u64)
+        );
+        assert_ne!(
+            StableState::default().hash_one("ab"),
+            StableState::default().hash_one("ba")
+        );
+    }
+
+    #[test]
+    fn identity() {
+        assert_eq!(BuildIdentityHasher::default().hash_one(42u64), 42);
+        assert_eq!(
+            BuildIdentityHasher::default().hash_one(-1i32),
+            u32::MAX as u64
+        );
+
+        let mut map = IntMap::default();
+        for id in 0..1000u32 {
+            map.insert(id, id);
+        }
+        assert_eq!(map.stats().max_chain_len, 1);
+        assert_eq!(map.get(&500), Some(&500));
+
+        // 字符串和元组的每个字段都要参与哈希，不能只剩最后一次写入
+        let hash = |key: &dyn Fn(&mut IdentityHasher)| {
+            let mut hasher = IdentityHasher::default();
+            key(&mut hasher);
+            hasher.finish()
+        };
+        assert_ne!(hash(&|h| "a".hash(h)), hash(&|h| "b".hash(h)));
+        assert_ne!(
+            hash(&|h| (1u32, 2u32).hash(h)),
+            hash(&|h| (3u32, 2u32).hash(h))
+        );
+
+        let mut map: IntMap<String, usize> = IntMap::default();
+        for i in 0..1000 {
+            map.insert(i.to_string(), i);
+        }
+        assert!(map.stats().occupied_buckets > 1);
+        assert_eq!(map.get("500"), Some(&500));
+    }
+
+    #[cfg(all(feature = "ahash", feature = "rustc-hash"))]
+    #[test]
+    fn fast_hashers() {
+        let mut a = AHashMap::default();
+        let mut fx = FxHashMap::default();
+        for i in 0..100 {
+            a.insert(i, i);
+            fx.insert(i.to_string(), i);
+        }
+        assert_eq!(a.get(&42), Some(&42));
+        assert_eq!(fx.get("42"), Some(&42));
+    }
+
+    #[test]
+    fn pre_hashed() {
+        let foo = PreHashed::new("foo".to_string());
+        assert_eq!(foo.hash(), make_hash("foo"));
+        assert_eq!(BuildIdentityHasher::default().hash_one(&foo), foo.hash());
+
+        let mut map = PreHashedMap::default();
+        map.insert(foo.clone(), 1);
+        map.insert(PreHashed::new("bar".to_string()), 2);
+        assert_eq!(map.get(&foo), Some(&1));
+        assert_eq!(map.get(&PreHashed::new("bar".to_string())), Some(&2));
+        assert_eq!(foo.len(), 3);
+    }
+}
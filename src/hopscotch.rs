@@ -0,0 +1,280 @@
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+    mem,
+};
+
+use crate::DefaultHashBuilder;
+
+// 邻域大小：每个 entry 离它的 home 槽位不超过 NEIGHBORHOOD - 1
+const NEIGHBORHOOD: usize = 32;
+
+// 跳房子哈希：开放寻址，但保证每个 entry 都在 home 槽位之后 NEIGHBORHOOD 个槽位以内，
+// hops[i] 的第 d 位表示槽位 i + d 上放着一个 home 为 i 的 entry。
+// 查找只看一个缓存友好的小窗口，负载因子很高时也不会退化成长距离探测；
+// 插入时找到的空位太远，就把窗口里别的 entry 往后挪，一步步把空位换到邻域里，挪不动了再扩容。
+// 槽位下标都按表长取模，表尾和表头是连着的
+pub struct HopscotchMap<K, V, S = DefaultHashBuilder> {
+    slots: Vec<Option<(K, V)>>,
+    hops: Vec<u32>,
+    items: usize,
+    hash_builder: S,
+}
+
+impl<K, V> HopscotchMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> HopscotchMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HopscotchMap {
+            slots: Vec::new(),
+            hops: Vec::new(),
+            items: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.slots.iter(),
+        }
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+}
+
+impl<K, V, S> Default for HopscotchMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> HopscotchMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn home<Q>(&self, key: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(key) as usize & self.mask()
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let home = self.home(key);
+        let mut hops = self.hops[home];
+        while hops != 0 {
+            let idx = (home + hops.trailing_zeros() as usize) & self.mask();
+            if matches!(&self.slots[idx], Some((k, _)) if k.borrow() == key) {
+                return Some(idx);
+            }
+            hops &= hops - 1;
+        }
+        None
+    }
+
+    // 把 home 之后的某个空位换到邻域里，返回它的下标；换不过来时返回 None
+    fn free_in_neighborhood(&mut self, home: usize) -> Option<usize> {
+        let mask = self.mask();
+        let mut dist = (0..self.slots.len()).find(|&d| self.slots[(home + d) & mask].is_none())?;
+        while dist >= NEIGHBORHOOD {
+            let free = (home + dist) & mask;
+            // 在空位之前的 NEIGHBORHOOD - 1 个槽位里找一个可以挪到空位上的 entry：
+            // 它的 home 为 b，挪过去之后离 b 仍然不超过 NEIGHBORHOOD - 1
+            let moved = (1..NEIGHBORHOOD).rev().find_map(|back| {
+                let bucket = free.wrapping_sub(back) & mask;
+                let hops = self.hops[bucket] & ((1 << back) - 1);
+                (hops != 0).then(|| (bucket, hops.trailing_zeros() as usize, back))
+            });
+            let (bucket, offset, back) = moved?;
+            let from = (bucket + offset) & mask;
+            self.slots[free] = self.slots[from].take();
+            self.hops[bucket] &= !(1 << offset);
+            self.hops[bucket] |= 1 << back;
+            dist -= back - offset;
+        }
+        Some((home + dist) & mask)
+    }
+
+    fn place(&mut self, entry: (K, V)) -> Result<(), (K, V)> {
+        let home = self.home(&entry.0);
+        match self.free_in_neighborhood(home) {
+            Some(idx) => {
+                self.slots[idx] = Some(entry);
+                self.hops[home] |= 1 << ((idx.wrapping_sub(home)) & self.mask());
+                Ok(())
+            }
+            None => Err(entry),
+        }
+    }
+
+    // 表长翻倍（至少 NEIGHBORHOOD）后重新放入所有 entry 和 extra；还放不下就继续翻倍
+    fn resize(&mut self, extra: Option<(K, V)>) {
+        let mut len = (2 * self.slots.len()).max(NEIGHBORHOOD);
+        let mut entries: Vec<_> = mem::take(&mut self.slots).into_iter().flatten().collect();
+        entries.extend(extra);
+        'retry: loop {
+            self.slots = (0..len).map(|_| None).collect();
+            self.hops = vec![0; len];
+            while let Some(entry) = entries.pop() {
+                if let Err(entry) = self.place(entry) {
+                    entries.push(entry);
+                    entries.extend(mem::take(&mut self.slots).into_iter().flatten());
+                    len *= 2;
+                    continue 'retry;
+                }
+            }
+            return;
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            let (_, old) = self.slots[idx].as_mut().unwrap();
+            return Some(mem::replace(old, value));
+        }
+        if 8 * (self.items + 1) > 7 * self.slots.len() {
+            self.resize(None);
+        }
+        if let Err(entry) = self.place((key, value)) {
+            self.resize(Some(entry));
+        }
+        self.items += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_ref().map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        self.slots[idx].as_mut().map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        let home = self.home(key);
+        self.hops[home] &= !(1 << (idx.wrapping_sub(home) & self.mask()));
+        self.items -= 1;
+        self.slots[idx].take().map(|(_, v)| v)
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HopscotchMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = HopscotchMap::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, Option<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.by_ref().flatten().next().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a HopscotchMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighborhood() {
+        let mut map = HopscotchMap::new();
+        for i in 0..10_000 {
+            assert_eq!(map.insert(i, i), None);
+        }
+        assert_eq!(map.insert(5, 50), Some(5));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.len(), 10_000);
+        assert_eq!(map.iter().count(), 10_000);
+        for i in 7..10_000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        // 每个 entry 都在它的 home 之后 NEIGHBORHOOD 个槽位以内
+        for (idx, slot) in map.slots.iter().enumerate() {
+            if let Some((k, _)) = slot {
+                let home = map.home(k);
+                assert!(idx.wrapping_sub(home) & map.mask() < NEIGHBORHOOD);
+            }
+        }
+
+        for i in 0..5000 {
+            assert!(map.remove(&i).is_some());
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 5000);
+        assert!(!map.contains_key(&10));
+        assert_eq!(map.get(&5000), Some(&5000));
+    }
+}
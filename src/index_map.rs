@@ -0,0 +1,249 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{make_hash, INITIAL_NBUCKETS};
+
+struct Slot<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+// entries 按插入顺序紧凑存放，indices 是旁路的哈希索引，桶里存的是 entries 的下标
+pub struct IndexMap<K, V> {
+    entries: Vec<Slot<K, V>>,
+    indices: Vec<Vec<usize>>,
+}
+
+impl<K, V> IndexMap<K, V> {
+    pub fn new() -> Self {
+        IndexMap {
+            entries: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|slot| (&slot.key, &slot.value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    fn bucket_of(&self, hash: u64) -> usize {
+        (hash % self.indices.len() as u64) as usize
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.indices.len() {
+            0 => INITIAL_NBUCKETS,
+            n => 2 * n,
+        };
+        self.indices = (0..target_size).map(|_| Vec::new()).collect();
+        for (idx, slot) in self.entries.iter().enumerate() {
+            let bucket = self.bucket_of(slot.hash);
+            self.indices[bucket].push(idx);
+        }
+    }
+
+    // 把桶里指向 from 的下标改成 to
+    fn relink(&mut self, hash: u64, from: usize, to: Option<usize>) {
+        let bucket = self.bucket_of(hash);
+        let bucket = &mut self.indices[bucket];
+        let pos = bucket.iter().position(|&idx| idx == from).unwrap();
+        match to {
+            Some(to) => bucket[pos] = to,
+            None => {
+                bucket.swap_remove(pos);
+            }
+        }
+    }
+}
+
+impl<K, V> Default for IndexMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IndexMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.indices.is_empty() {
+            return None;
+        }
+        self.indices[self.bucket_of(hash)]
+            .iter()
+            .copied()
+            .find(|&idx| {
+                let slot = &self.entries[idx];
+                slot.hash == hash && slot.key.borrow() == key
+            })
+    }
+
+    // 返回 entry 所在的下标，以及被覆盖的旧值；已存在的 key 保持原来的位置
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        let hash = make_hash(&key);
+        if let Some(idx) = self.find(hash, &key) {
+            let old = std::mem::replace(&mut self.entries[idx].value, value);
+            return (idx, Some(old));
+        }
+        if self.indices.is_empty() || self.entries.len() > 3 * self.indices.len() / 4 {
+            self.resize();
+        }
+        let idx = self.entries.len();
+        self.entries.push(Slot { hash, key, value });
+        let bucket = self.bucket_of(hash);
+        self.indices[bucket].push(idx);
+        (idx, None)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(make_hash(key), key)?;
+        Some(&self.entries[idx].value)
+    }
+
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(make_hash(key), key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index_of(key).is_some()
+    }
+
+    // O(1)，但会把最后一个 entry 挪到被删除的位置，打乱顺序
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = make_hash(key);
+        let idx = self.find(hash, key)?;
+        self.relink(hash, idx, None);
+        let last = self.entries.len() - 1;
+        if idx != last {
+            self.relink(self.entries[last].hash, last, Some(idx));
+        }
+        Some(self.entries.swap_remove(idx).value)
+    }
+
+    // O(n)，保持剩余 entry 的相对顺序
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = make_hash(key);
+        let idx = self.find(hash, key)?;
+        self.relink(hash, idx, None);
+        for bucket in self.indices.iter_mut() {
+            for i in bucket.iter_mut().filter(|i| **i > idx) {
+                *i -= 1;
+            }
+        }
+        Some(self.entries.remove(idx).value)
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, Slot<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|slot| (&slot.key, &slot.value))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a IndexMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for IndexMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = IndexMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order() {
+        let mut map = IndexMap::new();
+        for (i, k) in ["foo", "var", "dfs", "11", "bar"].into_iter().enumerate() {
+            assert_eq!(map.insert_full(k, i), (i, None));
+        }
+        assert_eq!(map.insert_full("dfs", 20), (2, Some(2)));
+        assert_eq!(map.get_index(2), Some((&"dfs", &20)));
+        let keys: Vec<_> = map.iter().map(|(&k, _)| k).collect();
+        assert_eq!(keys, ["foo", "var", "dfs", "11", "bar"]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: IndexMap<_, _> = (0..10).map(|i| (i, i * 10)).collect();
+        assert_eq!(map.shift_remove(&3), Some(30));
+        assert_eq!(map.swap_remove(&1), Some(10));
+        assert_eq!(map.swap_remove(&1), None);
+        let keys: Vec<_> = map.iter().map(|(&k, _)| k).collect();
+        assert_eq!(keys, [0, 9, 2, 4, 5, 6, 7, 8]);
+        for k in keys {
+            assert_eq!(map.get(&k), Some(&(k * 10)));
+            assert_eq!(
+                map.get_index(map.get_index_of(&k).unwrap()),
+                Some((&k, &(k * 10)))
+            );
+        }
+    }
+}
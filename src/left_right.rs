@@ -0,0 +1,260 @@
+use std::{borrow::Borrow, hash::Hash, ops::Deref, sync::PoisonError};
+
+// loom 模型测试时换成 loom 的同步原语，见下面的 loom_tests
+#[cfg(loom)]
+use loom::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+#[cfg(not(loom))]
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+
+use crate::HashMap;
+
+// 读写分离的两份表：读者只读 active 指向的那份，写者只改另一份并把操作记在 oplog 里，
+// refresh() 时交换两份表，再把 oplog 重放到换下来的那份上。
+// 读者用的那份表只在 refresh 之后、写者重放 oplog 时才会被写锁住，所以读几乎从不等待；
+// 代价是内存翻倍，并且 K、V 需要 Clone（每个操作要应用两次）
+struct Shared<K, V> {
+    tables: [RwLock<HashMap<K, V>>; 2],
+    active: AtomicUsize,
+}
+
+enum Op<K, V> {
+    Insert(K, V),
+    Remove(K),
+}
+
+pub fn new<K, V>() -> (WriteHandle<K, V>, ReadHandle<K, V>) {
+    let shared = Arc::new(Shared {
+        tables: [RwLock::new(HashMap::new()), RwLock::new(HashMap::new())],
+        active: AtomicUsize::new(0),
+    });
+    let reader = ReadHandle {
+        shared: Arc::clone(&shared),
+    };
+    let writer = WriteHandle {
+        shared,
+        oplog: Vec::new(),
+    };
+    (writer, reader)
+}
+
+// 表的内容在持锁期间只会被完整的 insert / remove 修改，poison 之后继续读写是安全的
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub struct ReadHandle<K, V> {
+    shared: Arc<Shared<K, V>>,
+}
+
+// 持有期间看到的是同一个版本；不要长时间持有，否则下一次 refresh 会等它释放
+pub struct ReadGuard<'a, K, V> {
+    guard: RwLockReadGuard<'a, HashMap<K, V>>,
+}
+
+impl<K, V> Deref for ReadGuard<'_, K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &HashMap<K, V> {
+        &self.guard
+    }
+}
+
+impl<K, V> ReadHandle<K, V> {
+    pub fn read(&self) -> ReadGuard<'_, K, V> {
+        loop {
+            let active = self.shared.active.load(Ordering::Acquire);
+            let guard = read(&self.shared.tables[active]);
+            // 拿锁之前写者可能已经把这份表换下去并开始写入还没发布的修改，
+            // 拿到锁之后它仍然是 active 才说明内容是已发布的版本，否则重试
+            if self.shared.active.load(Ordering::Acquire) == active {
+                return ReadGuard { guard };
+            }
+        }
+    }
+}
+
+impl<K, V> ReadHandle<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.read().get(key).cloned()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read().contains_key(key)
+    }
+}
+
+impl<K, V> Clone for ReadHandle<K, V> {
+    fn clone(&self) -> Self {
+        ReadHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+// 只有一个写者；修改在调用 refresh() 之前对读者不可见
+pub struct WriteHandle<K, V> {
+    shared: Arc<Shared<K, V>>,
+    oplog: Vec<Op<K, V>>,
+}
+
+impl<K, V> WriteHandle<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    // 写者是唯一修改 active 的一方，Relaxed 读就够了
+    fn write_inactive(&self) -> RwLockWriteGuard<'_, HashMap<K, V>> {
+        let inactive = 1 - self.shared.active.load(Ordering::Relaxed);
+        self.shared.tables[inactive]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.write_inactive().insert(key.clone(), value.clone());
+        self.oplog.push(Op::Insert(key, value));
+    }
+
+    pub fn remove(&mut self, key: K) {
+        self.write_inactive().remove(&key);
+        self.oplog.push(Op::Remove(key));
+    }
+
+    // 把目前为止的修改发布给读者
+    pub fn refresh(&mut self) {
+        let active = self.shared.active.load(Ordering::Relaxed);
+        self.shared.active.store(1 - active, Ordering::Release);
+        // 换下来的那份表上可能还有读者，拿写锁时会等它们读完；整个 oplog 在同一把锁里重放
+        let oplog = std::mem::take(&mut self.oplog);
+        let mut table = self.write_inactive();
+        for op in oplog {
+            match op {
+                Op::Insert(key, value) => {
+                    table.insert(key, value);
+                }
+                Op::Remove(key) => {
+                    table.remove(&key);
+                }
+            }
+        }
+    }
+
+    // 还没有 refresh 的修改个数
+    pub fn pending(&self) -> usize {
+        self.oplog.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn publish_on_refresh() {
+        let (mut writer, reader) = new();
+        writer.insert("a", 1);
+        assert_eq!(reader.get("a"), None);
+        writer.refresh();
+        assert_eq!(reader.get("a"), Some(1));
+
+        writer.insert("b", 2);
+        writer.remove("a");
+        assert_eq!(writer.pending(), 2);
+        writer.refresh();
+        assert_eq!(reader.get("a"), None);
+        assert_eq!(reader.read().len(), 1);
+        writer.refresh();
+        assert_eq!(reader.get("b"), Some(2));
+    }
+
+    #[test]
+    fn concurrent_readers() {
+        let (mut writer, reader) = new();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                let reader = reader.clone();
+                s.spawn(move || {
+                    for _ in 0..1000 {
+                        let map = reader.read();
+                        // 每次 refresh 前都插入了两个 key，读者不会看到一半的状态
+                        assert_eq!(map.len() % 2, 0);
+                    }
+                });
+            }
+            for i in 0..500 {
+                writer.insert(2 * i, i);
+                writer.insert(2 * i + 1, i);
+                writer.refresh();
+            }
+        });
+        assert_eq!(reader.read().len(), 1000);
+    }
+}
+
+// 运行：RUSTFLAGS="--cfg loom" cargo test --release --lib left_right::loom_tests
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use loom::thread;
+
+    use super::*;
+
+    // 读者和 refresh 任意交错，都只能看到完整发布的版本；
+    // 读者拿到旧的 active 之后，那份表可能已经被换下来并写入了还没发布的 3
+    #[test]
+    fn read_during_refresh() {
+        loom::model(|| {
+            let (mut writer, reader) = new();
+            let handle = thread::spawn(move || {
+                let map = reader.read();
+                assert!(map.is_empty() || map.len() == 2);
+                assert!(!map.contains_key(&3));
+            });
+            writer.insert(1, 1);
+            writer.insert(2, 2);
+            writer.refresh();
+            writer.insert(3, 3);
+            handle.join().unwrap();
+        });
+    }
+
+    // 连续两次 refresh：第二次要在换下来的表上重放 oplog，读者可能还拿着这份表的读锁
+    #[test]
+    fn replay_while_reading() {
+        loom::model(|| {
+            let (mut writer, reader) = new();
+            writer.insert(1, 1);
+            writer.refresh();
+            let handle = thread::spawn(move || {
+                let value = reader.get(&1);
+                assert!(value == Some(1) || value.is_none());
+                reader
+            });
+            writer.remove(1);
+            writer.refresh();
+            let reader = handle.join().unwrap();
+            assert_eq!(reader.get(&1), None);
+            writer.refresh();
+            assert_eq!(reader.get(&1), None);
+        });
+    }
+}
@@ -1,25 +1,241 @@
 use std::{
     borrow::Borrow,
-    hash::{DefaultHasher, Hash, Hasher},
+    fmt,
+    hash::{BuildHasher, DefaultHasher, Hash, Hasher},
+    sync::Arc,
 };
 
+#[macro_use]
+mod macros;
+
+pub mod arena;
+pub mod bimap;
+pub mod bloom;
+pub mod bounded;
+pub mod concurrent;
+pub mod counter;
+pub mod cow;
+pub mod cuckoo;
+pub mod disk;
+pub mod durable;
+mod expiring;
+mod external_trait_impls;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod flat;
+pub mod frozen;
+pub mod growth;
+pub mod hash;
+pub mod hopscotch;
+pub mod index_map;
+pub mod left_right;
+pub mod linked;
+#[cfg(feature = "memmap2")]
+pub mod mmap;
+pub mod multimap;
+pub mod mvcc;
+pub mod normalized;
+pub mod observed;
+pub mod persistent;
+pub mod phf;
+pub mod prefix;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod ring;
+pub mod scoped;
+pub mod set;
+#[cfg(feature = "async")]
+pub mod singleflight;
+pub mod small_str;
+pub mod snapshot;
+mod timer_wheel;
+pub mod two_choice;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weak;
+pub mod weighted;
+
+pub use arena::{Arena, ArenaMap};
+pub use bimap::BiMap;
+pub use bloom::BloomMap;
+pub use bounded::{BoundedHashMap, CapacityExceeded};
+pub use counter::Counter;
+pub use cow::CowMap;
+pub use cuckoo::CuckooMap;
+pub use disk::DiskMap;
+pub use durable::{DurableMap, SyncPolicy};
+pub use expiring::ExpiringMap;
+pub use flat::FlatMap;
+pub use frozen::{FrozenMap, FrozenMapBuilder};
+pub use growth::GrowthPolicy;
+pub use hash::{FixedState, IntMap, PreHashed, PreHashedMap, StableState};
+pub use hopscotch::HopscotchMap;
+pub use index_map::IndexMap;
+pub use linked::LinkedHashMap;
+#[cfg(feature = "memmap2")]
+pub use mmap::MmapMap;
+pub use multimap::MultiMap;
+pub use mvcc::VersionedMap;
+pub use normalized::{CaseInsensitiveMap, NormalizedMap};
+pub use observed::{MapHooks, ObservedMap};
+pub use phf::StaticMap;
+pub use prefix::PrefixMap;
+pub use ring::HashRing;
+pub use scoped::ScopedMap;
+pub use set::HashSet;
+#[cfg(feature = "async")]
+pub use singleflight::SingleFlight;
+pub use small_str::{SmallStr, SmallStrMap};
+pub use snapshot::Codec;
+pub use two_choice::TwoChoiceMap;
+pub use weak::WeakValueMap;
+pub use weighted::WeightedCache;
+
+#[cfg(feature = "proptest")]
+pub mod proptest {
+    pub use crate::external_trait_impls::proptest::*;
+}
+
+#[cfg(feature = "rayon")]
+pub mod rayon {
+    pub use crate::external_trait_impls::rayon::*;
+}
+
 const INITIAL_NBUCKETS: usize = 1;
 
+// 和 std 一样默认用随机种子的 SipHash，防止 HashDoS；
+// 打开 deterministic feature 后改成固定种子，方便复现
+#[cfg(not(feature = "deterministic"))]
+pub type DefaultHashBuilder = std::hash::RandomState;
+#[cfg(feature = "deterministic")]
+pub type DefaultHashBuilder = FixedState;
+
+// 负载因子不超过 3/4 时，链长超过这个值基本说明哈希分布有问题
+#[cfg(feature = "tracing")]
+const LONG_CHAIN_LEN: usize = 8;
+
+// 不超过这么多个 entry 时只用一个桶：查找就是顺序比较 key，完全不计算哈希，
+// 对 key 是长字符串的小表来说，省下的哈希比多比较几次 key 划算
+const SMALL_LEN: usize = 8;
+
+// 装下 capacity 个元素而不触发 resize 所需的桶数
+fn buckets_for(capacity: usize) -> usize {
+    if capacity <= SMALL_LEN {
+        return INITIAL_NBUCKETS;
+    }
+    let mut nbuckets = INITIAL_NBUCKETS;
+    while 3 * nbuckets / 4 < capacity.saturating_sub(1) {
+        nbuckets *= 2;
+    }
+    nbuckets
+}
+
+pub(crate) fn make_hash<Q>(key: &Q) -> u64
+where
+    Q: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
 pub struct OccupiedEntry<'a, K, V> {
-    element: &'a mut (K, V),
+    key: Option<K>, // 调用 entry 时传入的 key，replace_key 会用它换掉表里的 key；first_entry 得到的 entry 没有
+    bucket: &'a mut Vec<(K, V)>,
+    idx: usize,
+    items: &'a mut usize,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.bucket[self.idx].0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.bucket[self.idx].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.bucket[self.idx].1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.bucket[self.idx].1
+    }
+
+    // 返回旧值
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        *self.items -= 1;
+        self.bucket.swap_remove(self.idx)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    // 用传给 entry 的 key 替换表里的 key，返回原来的 key；适合 interner 这类需要统一成规范实例的场景。
+    // 不是通过 entry(key) 得到的 entry 没有可以换进去的 key，会 panic
+    pub fn replace_key(self) -> K {
+        let key = self.key.expect("entry was not created from a key");
+        std::mem::replace(&mut self.bucket[self.idx].0, key)
+    }
+
+    // f 返回 None 时删除这个 entry，得到对应的 VacantEntry
+    pub fn replace_entry_with<F>(self, f: F) -> Entry<'a, K, V>
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        let (key, value) = self.bucket.swap_remove(self.idx);
+        match f(&key, value) {
+            Some(value) => {
+                self.bucket.push((key, value));
+                let idx = self.bucket.len() - 1;
+                Entry::Occupied(OccupiedEntry {
+                    key: self.key,
+                    bucket: self.bucket,
+                    idx,
+                    items: self.items,
+                })
+            }
+            None => {
+                *self.items -= 1;
+                Entry::Vacant(VacantEntry {
+                    key,
+                    bucket: self.bucket,
+                    items: self.items,
+                })
+            }
+        }
+    }
 }
 
 pub struct VacantEntry<'a, K, V> {
     key: K,
-    map: &'a mut HashMap<K, V>,
-    bucket: usize, // 必须的，因为需要在空值时插入 value
+    bucket: &'a mut Vec<(K, V)>, // 必须的，因为需要在空值时插入 value
+    items: &'a mut usize,
 }
 
 impl<'a, K, V> VacantEntry<'a, K, V> {
     fn insert(self, value: V) -> &'a mut V {
-        self.map.buckets[self.bucket].push((self.key, value));
-        self.map.items += 1;
-        &mut self.map.buckets[self.bucket].last_mut().unwrap().1
+        self.bucket.push((self.key, value));
+        *self.items += 1;
+        &mut self.bucket.last_mut().unwrap().1
+    }
+
+    // key 已经移进表里，得到的 OccupiedEntry 不能再 replace_key
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V> {
+        self.bucket.push((self.key, value));
+        *self.items += 1;
+        OccupiedEntry {
+            key: None,
+            idx: self.bucket.len() - 1,
+            bucket: self.bucket,
+            items: self.items,
+        }
     }
 }
 
@@ -29,11 +245,46 @@ pub enum Entry<'a, K, V> {
     Vacant(VacantEntry<'a, K, V>),
 }
 
+impl<K, V> fmt::Debug for OccupiedEntry<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<K, V> fmt::Debug for VacantEntry<'_, K, V>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(&self.key).finish()
+    }
+}
+
+impl<K, V> fmt::Debug for Entry<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Occupied(e) => f.debug_tuple("Entry").field(e).finish(),
+            Self::Vacant(e) => f.debug_tuple("Entry").field(e).finish(),
+        }
+    }
+}
+
 impl<'a, K, V> Entry<'a, K, V> {
     // or_insert总是会构建参数 value，不管当前 Entry 是否是空的: et.or_insert(Vec::new()) 总是会执行 Vec::new()
     pub fn or_insert(self, value: V) -> &'a mut V {
         match self {
-            Self::Occupied(e) => &mut e.element.1,
+            Self::Occupied(e) => e.into_mut(),
             Self::Vacant(e) => e.insert(value),
         }
     }
@@ -43,70 +294,494 @@ impl<'a, K, V> Entry<'a, K, V> {
         F: FnOnce() -> V,
     {
         match self {
-            Self::Occupied(e) => &mut e.element.1,
+            Self::Occupied(e) => e.into_mut(),
             Self::Vacant(e) => e.insert(maker()),
         }
     }
 
+    // maker 失败时不插入任何东西，把错误原样返回
+    pub fn or_try_insert_with<F, E>(self, maker: F) -> Result<&'a mut V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        match self {
+            Self::Occupied(e) => Ok(e.into_mut()),
+            Self::Vacant(e) => Ok(e.insert(maker()?)),
+        }
+    }
+
     pub fn or_default(self) -> &'a mut V
     where
         V: Default,
     {
         self.or_insert_with(V::default)
     }
+
+    // 不管原来有没有都写入 value，并且保留指向这个位置的 entry
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V> {
+        match self {
+            Self::Occupied(mut e) => {
+                e.insert(value);
+                e
+            }
+            Self::Vacant(e) => e.insert_entry(value),
+        }
+    }
+}
+
+// union_with 的结果：key 只在左边、只在右边、或者两边都有
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EitherOrBoth<L, R> {
+    Left(L),
+    Right(R),
+    Both(L, R),
+}
+
+// diff 的结果，changed 里依次是 key、旧值、新值
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDiff<'a, K, V> {
+    pub added: Vec<(&'a K, &'a V)>,
+    pub removed: Vec<(&'a K, &'a V)>,
+    pub changed: Vec<(&'a K, &'a V, &'a V)>,
+}
+
+impl<K, V> MapDiff<'_, K, V> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// try_from_iter 遇到重复 key 时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKeys<K> {
+    pub keys: Vec<K>,
+}
+
+impl<K> fmt::Display for DuplicateKeys<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} key(s) appear more than once", self.keys.len())
+    }
+}
+
+impl<K> std::error::Error for DuplicateKeys<K> where K: fmt::Debug {}
+
+// 桶的分布情况，用来诊断哈希函数分布不均或者被构造的恶意 key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapStats {
+    pub buckets: usize,
+    pub occupied_buckets: usize,
+    pub max_chain_len: usize,
+    // 按所有桶（包括空桶）计算，即负载因子
+    pub mean_chain_len: f64,
+    pub chain_len_variance: f64,
 }
 
-pub struct HashMap<K, V> {
+// V 是零大小类型（比如 HashMap<K, ()>）时，(K, V) 和 K 的布局相同，链表里不会为 value 多占空间
+#[derive(Clone)]
+pub struct HashMap<K, V, S = DefaultHashBuilder> {
     buckets: Vec<Vec<(K, V)>>,
     items: usize,
+    hash_builder: S,
+    // None 表示默认的 Doubling，这样默认构造的表不需要额外分配
+    growth: Option<Arc<dyn GrowthPolicy>>,
 }
 
 impl<K, V> HashMap<K, V> {
     pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    // 预先分配足够的桶，插入 capacity 个元素之前不会触发 resize
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
         HashMap {
             buckets: Vec::new(),
             items: 0,
+            hash_builder,
+            growth: None,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        if capacity == 0 {
+            return Self::with_hasher(hash_builder);
+        }
+        HashMap {
+            buckets: (0..buckets_for(capacity)).map(|_| Vec::new()).collect(),
+            items: 0,
+            hash_builder,
+            growth: None,
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    // 替换自动扩容时使用的增长策略，clone 出来的表共用同一个策略
+    pub fn set_growth_policy<P>(&mut self, policy: P)
+    where
+        P: GrowthPolicy + 'static,
+    {
+        self.growth = Some(Arc::new(policy));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.into_iter()
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    // 把 key 当作集合看，和另一张表的 key 做集合比较
+    pub fn keys_set(&self) -> KeySet<'_, K, V, S> {
+        KeySet { map: self }
+    }
+
+    // 反向查找要扫描整张表，O(n)
+    pub fn contains_value(&self, value: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.values().any(|v| v == value)
+    }
+
+    // 按迭代顺序返回第一个满足 pred 的 entry
+    pub fn find<F>(&self, mut pred: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.iter().find(|(k, v)| pred(k, v))
+    }
+
+    // 按 key 排好序再遍历，方便输出稳定的结果；每次调用都会收集并排序一遍
+    pub fn iter_sorted_by_key(&self) -> std::vec::IntoIter<(&K, &V)>
+    where
+        K: Ord,
+    {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter()
+    }
+
+    pub fn into_sorted_vec(self) -> Vec<(K, V)>
+    where
+        K: Ord,
+    {
+        let mut entries: Vec<_> = self.into_iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    // 把桶数组切成 n 段连续的只读视图，每个 entry 恰好落在其中一段里；
+    // 视图是 Send 的（K、V: Sync 时），可以分给自己的线程池并行遍历，不需要 rayon
+    pub fn partitions(&self, n: usize) -> Vec<Partition<'_, K, V>> {
+        assert!(n > 0, "partition count must be positive");
+        let len = self.buckets.len();
+        (0..n)
+            .map(|i| Partition {
+                buckets: &self.buckets[i * len / n..(i + 1) * len / n],
+            })
+            .collect()
+    }
+
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, K, V> {
+        let mut cursor = CursorMut {
+            buckets: &mut self.buckets,
+            items: &mut self.items,
+            bucket_idx: 0,
+            at: 0,
+        };
+        cursor.skip_empty();
+        cursor
+    }
+
+    // 桶数组和各个链表占用的堆内存，不包括 K、V 自己持有的堆内存
+    pub fn allocated_bytes(&self) -> usize {
+        let array = self.buckets.capacity() * std::mem::size_of::<Vec<(K, V)>>();
+        let chains: usize = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.capacity() * std::mem::size_of::<(K, V)>())
+            .sum();
+        array + chains
+    }
+
+    // 和 allocated_bytes 一样，再加上 heap_size 报告的每个 entry 自己持有的堆内存
+    pub fn allocated_bytes_with<F>(&self, mut heap_size: F) -> usize
+    where
+        F: FnMut(&K, &V) -> usize,
+    {
+        self.allocated_bytes() + self.iter().map(|(k, v)| heap_size(k, v)).sum::<usize>()
+    }
+
+    // 内容指纹：每个 entry 单独哈希后相加，和桶布局、迭代顺序都无关；
+    // 用 FixedState 这类不随进程变化的哈希器，才能在不同副本之间比较
+    pub fn content_hash<H>(&self) -> u64
+    where
+        K: Hash,
+        V: Hash,
+        H: BuildHasher + Default,
+    {
+        let hash_builder = H::default();
+        let sum = self.iter().fold(0u64, |sum, entry| {
+            sum.wrapping_add(hash_builder.hash_one(entry))
+        });
+        hash_builder.hash_one((self.items, sum))
+    }
+
+    pub fn stats(&self) -> MapStats {
+        let buckets = self.buckets.len();
+        let mean = if buckets == 0 {
+            0.0
+        } else {
+            self.items as f64 / buckets as f64
+        };
+        let variance = if buckets == 0 {
+            0.0
+        } else {
+            self.buckets
+                .iter()
+                .map(|bucket| (bucket.len() as f64 - mean).powi(2))
+                .sum::<f64>()
+                / buckets as f64
+        };
+        MapStats {
+            buckets,
+            occupied_buckets: self.buckets.iter().filter(|b| !b.is_empty()).count(),
+            max_chain_len: self.buckets.iter().map(Vec::len).max().unwrap_or(0),
+            mean_chain_len: mean,
+            chain_len_variance: variance,
+        }
+    }
+
+    // 从最后一个非空的桶尾部取出一个 entry，不需要哈希
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let entry = self.buckets.iter_mut().rev().find_map(Vec::pop)?;
+        self.items -= 1;
+        Some(entry)
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for bucket in self.buckets.iter_mut() {
+            let before = bucket.len();
+            bucket.retain_mut(|(k, v)| f(k, v));
+            self.items -= before - bucket.len();
+        }
+    }
+
+    // 按值把每个 value 交给 f：返回 Some 就换成新值，返回 None 就删掉这个 entry。
+    // 每个桶就地 collect，复用原来链表的内存；f panic 时正在处理的那个桶里的 entry 会丢失，
+    // 桶在交给 f 之前就先从 items 里减掉，所以 panic 之后 len() 仍然和表里实际的 entry 数一致
+    pub fn retain_map<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, V) -> Option<V>,
+    {
+        for bucket in self.buckets.iter_mut() {
+            let taken = std::mem::take(bucket);
+            self.items -= taken.len();
+            *bucket = taken
+                .into_iter()
+                .filter_map(|(k, v)| f(&k, v).map(|v| (k, v)))
+                .collect();
+            self.items += bucket.len();
+        }
+    }
+
+    // key 不变，所以每个 entry 留在原来的桶里，不需要重新哈希
+    pub fn into_map_values<U, F>(self, mut f: F) -> HashMap<K, U, S>
+    where
+        F: FnMut(&K, V) -> U,
+    {
+        let buckets = self
+            .buckets
+            .into_iter()
+            .map(|bucket| {
+                bucket
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let u = f(&k, v);
+                        (k, u)
+                    })
+                    .collect()
+            })
+            .collect();
+        HashMap {
+            buckets,
+            items: self.items,
+            hash_builder: self.hash_builder,
+            growth: self.growth,
+        }
+    }
+
+    // 新 key 需要重新哈希；f 把两个 key 映射成同一个新 key 时，后遍历到的 entry 覆盖先遍历到的
+    pub fn map_keys<K2, F>(self, mut f: F) -> HashMap<K2, V, S>
+    where
+        K2: Hash + Eq,
+        S: BuildHasher,
+        F: FnMut(K) -> K2,
+    {
+        let mut map = HashMap::with_hasher(self.hash_builder);
+        map.growth = self.growth;
+        map.reserve(self.items);
+        for (k, v) in self.buckets.into_iter().flatten() {
+            map.insert(f(k), v);
         }
+        map
+    }
+}
+
+impl<K, V, S> Default for HashMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for HashMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<K, V> HashMap<K, V>
+impl<K, V, S> HashMap<K, V, S>
 where
     K: Hash + Eq,
+    S: BuildHasher,
 {
     fn bucket_idx<Q>(&self, key: &Q) -> Option<usize>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        if self.buckets.is_empty() {
-            return None;
+        match self.buckets.len() {
+            0 => None,
+            1 => Some(0),
+            n => Some((self.hash_builder.hash_one(key) % n as u64) as usize),
+        }
+    }
+
+    // 只有一个桶时按 SMALL_LEN 判断，之后按 3/4 的负载因子
+    fn needs_resize(&self) -> bool {
+        match self.buckets.len() {
+            0 => true,
+            1 => self.items >= SMALL_LEN,
+            n => self.items > 3 * n / 4,
         }
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        Some((hasher.finish() % self.buckets.len() as u64) as usize)
     }
+
     fn resize(&mut self) {
-        let target_size = match self.buckets.len() {
-            0 => INITIAL_NBUCKETS,
-            n => 2 * n,
+        let target_size = match (self.buckets.len(), &self.growth) {
+            (0, _) => INITIAL_NBUCKETS,
+            // 离开单桶模式时一步扩到负载因子以内，不然接下来几次 insert 会接连扩容
+            (1, None) => buckets_for(self.items + 1),
+            (n, Some(growth)) => growth.grow(n),
+            (n, None) => 2 * n,
         };
+        if target_size > self.buckets.len() {
+            self.resize_to(target_size);
+        }
+    }
+
+    fn resize_to(&mut self, target_size: usize) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "resize",
+            old_buckets = self.buckets.len(),
+            new_buckets = target_size,
+            items = self.items
+        )
+        .entered();
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
 
         let mut new_buckets = Vec::with_capacity(target_size);
         new_buckets.extend((0..target_size).map(|_| Vec::new()));
 
         for (key, value) in self.buckets.iter_mut().flat_map(|bucket| bucket.drain(..)) {
-            let mut hasher = DefaultHasher::new();
-            key.hash(&mut hasher);
-            let bucket_id = (hasher.finish() % new_buckets.len() as u64) as usize;
+            let bucket_id = match target_size {
+                1 => 0,
+                n => (self.hash_builder.hash_one(&key) % n as u64) as usize,
+            };
             new_buckets[bucket_id].push((key, value));
         }
 
         let _ = std::mem::replace(&mut self.buckets, new_buckets);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?start.elapsed(), "resized");
+    }
+
+    // 桶数不变，把所有 entry 重新分配一遍，顺带收回各个链表多余的容量
+    pub fn rehash_in_place(&mut self) {
+        self.resize_to(self.buckets.len());
+    }
+
+    // 换一个新的种子后重新哈希，用于发现链表聚集严重或者怀疑遭到 HashDoS 之后；
+    // 对 RandomState 来说每次 default() 都会得到不同的种子
+    pub fn reseed(&mut self)
+    where
+        S: Default,
+    {
+        self.hash_builder = S::default();
+        self.rehash_in_place();
+    }
+
+    // 保证再插入 additional 个元素之前不会触发 resize；设置了增长策略时最多扩到策略允许的桶数
+    pub fn reserve(&mut self, additional: usize) {
+        let target_size = self.buckets_for_policy(self.items + additional);
+        if target_size > self.buckets.len() {
+            self.resize_to(target_size);
+        }
+    }
+
+    // 按增长策略从当前桶数一步步长到够装下 capacity 个元素，和逐个 insert 扩容的结果一样；
+    // 策略不再增长（比如到了 Capped 的上限）就停在那里
+    fn buckets_for_policy(&self, capacity: usize) -> usize {
+        let wanted = buckets_for(capacity);
+        let Some(growth) = &self.growth else {
+            return wanted;
+        };
+        let mut nbuckets = self.buckets.len().max(INITIAL_NBUCKETS);
+        while nbuckets < wanted {
+            let next = growth.grow(nbuckets);
+            if next <= nbuckets {
+                break;
+            }
+            nbuckets = next;
+        }
+        nbuckets
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        if self.buckets.is_empty() || self.items > 3 * self.buckets.len() / 4 {
+        if self.needs_resize() {
             self.resize();
         }
         let bucket_idx = self.bucket_idx(&key)?;
@@ -119,9 +794,71 @@ where
         }
         bucket.push((key, value));
         self.items += 1;
+
+        #[cfg(feature = "tracing")]
+        if bucket.len() > LONG_CHAIN_LEN {
+            tracing::debug!(
+                chain_len = bucket.len(),
+                buckets = self.buckets.len(),
+                items = self.items,
+                "unusually long collision chain"
+            );
+        }
+        None
+    }
+
+    // 和 insert 一样，但 key 已存在时把表里的 key 也换成新的，返回旧的 (key, value)
+    pub fn replace(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(bucket_idx) = self.bucket_idx(&key) {
+            let bucket = &mut self.buckets[bucket_idx];
+            if let Some(entry) = bucket.iter_mut().find(|(ekey, _)| *ekey == key) {
+                return Some(std::mem::replace(entry, (key, value)));
+            }
+        }
+        self.insert_unique_unchecked(key, value);
         None
     }
 
+    // 批量插入：按 len() 一次扩容到位，之后每个 key 只哈希一次；
+    // len() 报小了也不会丢数据，超出的部分照常按负载因子扩容
+    pub fn insert_batch<I>(&mut self, pairs: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let pairs = pairs.into_iter();
+        self.reserve(pairs.len());
+        for (key, value) in pairs {
+            // len() 准确时这里不会触发 resize
+            if self.needs_resize() {
+                self.resize();
+            }
+            let bucket_idx = self.bucket_idx(&key).unwrap();
+            let bucket = &mut self.buckets[bucket_idx];
+            match bucket.iter_mut().find(|(ekey, _)| *ekey == key) {
+                Some((_, evalue)) => *evalue = value,
+                None => {
+                    bucket.push((key, value));
+                    self.items += 1;
+                }
+            }
+        }
+    }
+
+    // 跳过重复 key 的检查，直接追加到桶尾；调用方必须保证 key 不在表中，
+    // 否则表里会出现两个相同的 key，之后 get/remove 具体命中哪一个是不确定的（但不会有内存安全问题）
+    pub fn insert_unique_unchecked(&mut self, key: K, value: V) -> (&K, &mut V) {
+        if self.needs_resize() {
+            self.resize();
+        }
+        let bucket_idx = self.bucket_idx(&key).unwrap();
+        let bucket = &mut self.buckets[bucket_idx];
+        bucket.push((key, value));
+        self.items += 1;
+        let (k, v) = bucket.last_mut().unwrap();
+        (k, v)
+    }
+
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
         K: Borrow<Q>,
@@ -134,45 +871,139 @@ where
             .map(|(_, evalue)| evalue)
     }
 
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
         let bucket_idx = self.bucket_idx(key)?;
-        let bucket = &mut self.buckets[bucket_idx];
-        let pos = bucket.iter().position(|(ekey, _)| ekey.borrow() == key)?;
-        self.items -= 1;
-        Some(bucket.swap_remove(pos).1)
+        self.buckets[bucket_idx]
+            .iter_mut()
+            .find(|(ekey, _)| ekey.borrow() == key)
+            .map(|(_, evalue)| evalue)
     }
 
-    pub fn entry<'a>(&'a mut self, key: K) -> Entry<'a, K, V> {
-        if self.buckets.is_empty() || self.items > 3 * self.buckets.len() / 4 {
-            self.resize();
-        }
-        let bucket_idx = self.bucket_idx(&key).unwrap();
-        // 下面写法会报出对 bucket 的 second mutable borrow错误
-
+    // 一次查多个 key，结果和 keys 一一对应；先把所有 key 的桶算出来再逐个查找，
+    // 哈希和访存分成两轮，方便 CPU 把后面的桶提前取进缓存
+    pub fn get_many<Q>(&self, keys: &[&Q]) -> Vec<Option<&V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let buckets: Vec<_> = keys.iter().map(|key| self.bucket_idx(*key)).collect();
+        keys.iter()
+            .zip(buckets)
+            .map(|(key, bucket_idx)| {
+                self.buckets[bucket_idx?]
+                    .iter()
+                    .find(|(ekey, _)| ekey.borrow() == *key)
+                    .map(|(_, evalue)| evalue)
+            })
+            .collect()
+    }
+
+    // key 存在时原地修改 value，返回 key 是否存在
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&mut V),
+    {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    // 和 remove 一样，但同时把表里存的 key 还回来
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket_idx = self.bucket_idx(key)?;
+        let bucket = &mut self.buckets[bucket_idx];
+        let pos = bucket.iter().position(|(ekey, _)| ekey.borrow() == key)?;
+        self.items -= 1;
+        Some(bucket.swap_remove(pos))
+    }
+
+    // 等价于 entry(key).or_insert_with(maker)，但不构造 Entry，只计算一次哈希
+    pub fn get_or_insert_with<F>(&mut self, key: K, maker: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        if self.needs_resize() {
+            self.resize();
+        }
+        let bucket_idx = self.bucket_idx(&key).unwrap();
+        let bucket = &mut self.buckets[bucket_idx];
+        let idx = match bucket.iter().position(|(ekey, _)| *ekey == key) {
+            Some(idx) => idx,
+            None => {
+                bucket.push((key, maker()));
+                self.items += 1;
+                bucket.len() - 1
+            }
+        };
+        &mut bucket[idx].1
+    }
+
+    pub fn entry<'a>(&'a mut self, key: K) -> Entry<'a, K, V> {
+        if self.needs_resize() {
+            self.resize();
+        }
+        let bucket_idx = self.bucket_idx(&key).unwrap();
+        // 下面写法会报出对 bucket 的 second mutable borrow错误
+
         // match bucket.iter_mut().find(|(ekey, _)| *ekey == key) {
         //     Some(entry) => Entry::Occupied(OccupiedEntry { element: entry }),
         //     None => Entry::Vacant(VacantEntry { key, bucket }),
         // }
 
-        match self.buckets[bucket_idx]
-            .iter()
-            .position(|(ekey, _)| *ekey == key)
-        {
+        let bucket = &mut self.buckets[bucket_idx];
+        match bucket.iter().position(|(ekey, _)| *ekey == key) {
             Some(idx) => Entry::Occupied(OccupiedEntry {
-                element: &mut self.buckets[bucket_idx][idx],
+                key: Some(key),
+                bucket,
+                idx,
+                items: &mut self.items,
             }),
             None => Entry::Vacant(VacantEntry {
-                key: key,
-                map: self,
-                bucket: bucket_idx,
+                key,
+                bucket,
+                items: &mut self.items,
             }),
         }
     }
 
+    // 迭代顺序上的第一个 entry，表为空时返回 None；不需要知道 key 就能通过 entry API 查看、修改或删除，
+    // 适合淘汰策略和“每次取一个处理掉”的循环
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        let nbuckets = self.buckets.len();
+        let start = iter_start(&self.buckets);
+        let bucket_idx = (0..nbuckets)
+            .map(|i| (start + i) % nbuckets)
+            .find(|&i| !self.buckets[i].is_empty())?;
+        Some(OccupiedEntry {
+            key: None,
+            bucket: &mut self.buckets[bucket_idx],
+            idx: 0,
+            items: &mut self.items,
+        })
+    }
+
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
         K: Borrow<Q>,
@@ -181,26 +1012,225 @@ where
         self.get(key).is_some()
     }
 
-    pub fn len(&self) -> usize {
-        self.items
+    // 把满足 pred 的 entry 移到一个新表里返回，key 和 value 都是移动而不是克隆
+    pub fn split_off_if<F>(&mut self, mut pred: F) -> HashMap<K, V, S>
+    where
+        F: FnMut(&K, &V) -> bool,
+        S: Default,
+    {
+        let mut split = HashMap::with_hasher(S::default());
+        for bucket in self.buckets.iter_mut() {
+            let mut i = 0;
+            while i < bucket.len() {
+                let (k, v) = &bucket[i];
+                if pred(k, v) {
+                    let (k, v) = bucket.swap_remove(i);
+                    self.items -= 1;
+                    split.insert_unique_unchecked(k, v);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        split
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.items == 0
+    // 把 other 的 entry 全部移进来，key 冲突时用 f(key, 旧值, 新值) 的结果作为新的 value
+    pub fn merge<F>(&mut self, other: HashMap<K, V, S>, mut f: F)
+    where
+        F: FnMut(&K, V, V) -> V,
+    {
+        self.reserve(other.len());
+        for (key, value) in other {
+            let bucket_idx = self.bucket_idx(&key).unwrap();
+            let bucket = &mut self.buckets[bucket_idx];
+            match bucket.iter().position(|(ekey, _)| *ekey == key) {
+                Some(pos) => {
+                    let (ekey, old) = bucket.swap_remove(pos);
+                    let merged = f(&ekey, old, value);
+                    bucket.push((ekey, merged));
+                }
+                None => {
+                    bucket.push((key, value));
+                    self.items += 1;
+                }
+            }
+        }
+    }
+
+    // 下面三个都是惰性的：遍历一张表，对每个 key 去另一张表里查
+    pub fn intersection_with<'a, W>(
+        &'a self,
+        other: &'a HashMap<K, W, S>,
+    ) -> impl Iterator<Item = (&'a K, &'a V, &'a W)> {
+        self.iter()
+            .filter_map(move |(k, v)| other.get(k).map(|w| (k, v, w)))
+    }
+
+    // self 中有、other 中没有的 entry
+    pub fn difference<'a, W>(
+        &'a self,
+        other: &'a HashMap<K, W, S>,
+    ) -> impl Iterator<Item = (&'a K, &'a V)> {
+        self.iter().filter(move |(k, _)| !other.contains_key(*k))
+    }
+
+    pub fn union_with<'a, W>(
+        &'a self,
+        other: &'a HashMap<K, W, S>,
+    ) -> impl Iterator<Item = (&'a K, EitherOrBoth<&'a V, &'a W>)> {
+        let left = self.iter().map(move |(k, v)| match other.get(k) {
+            Some(w) => (k, EitherOrBoth::Both(v, w)),
+            None => (k, EitherOrBoth::Left(v)),
+        });
+        let right = other
+            .difference(self)
+            .map(|(k, w)| (k, EitherOrBoth::Right(w)));
+        left.chain(right)
+    }
+
+    // 把 self 当作旧版本、other 当作新版本做比较
+    pub fn diff<'a>(&'a self, other: &'a HashMap<K, V, S>) -> MapDiff<'a, K, V>
+    where
+        V: PartialEq,
+    {
+        MapDiff {
+            added: other.difference(self).collect(),
+            removed: self.difference(other).collect(),
+            changed: self
+                .intersection_with(other)
+                .filter(|(_, old, new)| old != new)
+                .collect(),
+        }
+    }
+
+    // 重新哈希成只读的紧凑布局，放进 Arc 里：克隆 Arc 就能在线程之间共享，读的时候不需要加锁
+    pub fn freeze(self) -> Arc<FrozenMap<K, V>> {
+        Arc::new(FrozenMap::from(self))
+    }
+
+    // 检查内部不变量，被破坏时直接 panic：items 等于所有桶的长度之和，每个 key 都在自己哈希到的桶里
+    #[cfg(debug_assertions)]
+    pub fn debug_validate(&self) {
+        let total: usize = self.buckets.iter().map(Vec::len).sum();
+        assert_eq!(self.items, total, "items does not match bucket lengths");
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            for (key, _) in bucket {
+                assert_eq!(
+                    self.bucket_idx(key),
+                    Some(idx),
+                    "key stored in the wrong bucket"
+                );
+            }
+        }
+    }
+
+    // 只在现有的桶装不下时才扩容一次，而不是边插入边多次 resize
+    pub fn extend_from_std<T>(&mut self, map: std::collections::HashMap<K, V, T>) {
+        self.reserve(map.len());
+        for (k, v) in map {
+            self.insert(k, v);
+        }
+    }
+}
+
+// 打开 randomize-iteration feature 后，debug 构建下每个 map 从一个随机的桶开始迭代，
+// 让不小心依赖迭代顺序的测试尽早失败（类似 Go）；起点由桶数组的地址决定，所以同一个 map 在 resize 之前顺序不变
+#[cfg(all(feature = "randomize-iteration", debug_assertions))]
+fn iter_start<T>(buckets: &[T]) -> usize {
+    static STATE: std::sync::OnceLock<std::hash::RandomState> = std::sync::OnceLock::new();
+    if buckets.is_empty() {
+        return 0;
+    }
+    let hash = STATE
+        .get_or_init(Default::default)
+        .hash_one(buckets.as_ptr() as usize);
+    (hash % buckets.len() as u64) as usize
+}
+
+#[cfg(not(all(feature = "randomize-iteration", debug_assertions)))]
+fn iter_start<T>(_buckets: &[T]) -> usize {
+    0
+}
+
+impl<K, V> HashMap<K, Vec<V>>
+where
+    K: Hash + Eq,
+{
+    // 把 (key, value) 按 key 分组，组内保持原来的顺序；按 size_hint 预留空间（假设 key 大多不重复）
+    pub fn group_by<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let iter = iter.into_iter();
+        let mut groups = HashMap::with_capacity(iter.size_hint().0);
+        for (k, v) in iter {
+            groups.get_or_insert_with(k, Vec::new).push(v);
+        }
+        groups
     }
 }
 
+// 还没迭代到的 entry 是 (bucket_idx, at) 到 (back_idx, back_at) 之间（左闭右开）的部分，
+// 桶号都是从 start 开始数的逻辑编号
 pub struct Iter<'a, K, V> {
-    map: &'a HashMap<K, V>,
+    buckets: &'a [Vec<(K, V)>],
     bucket_idx: usize,
     at: usize,
+    back_idx: usize,
+    back_at: usize,
+    start: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn rotated(&self, idx: usize) -> &'a [(K, V)] {
+        &self.buckets[(idx + self.start) % self.buckets.len()]
+    }
+
+    // 前端所在的桶，和后端在同一个桶时只到 back_at 为止
+    fn bucket(&self) -> Option<&'a [(K, V)]> {
+        if self.bucket_idx > self.back_idx || self.bucket_idx == self.buckets.len() {
+            return None;
+        }
+        let bucket = self.rotated(self.bucket_idx);
+        if self.bucket_idx == self.back_idx {
+            Some(&bucket[..self.back_at])
+        } else {
+            Some(bucket)
+        }
+    }
+}
+
+// 只复制位置，不要求 K、V: Clone
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            buckets: self.buckets,
+            bucket_idx: self.bucket_idx,
+            at: self.at,
+            back_idx: self.back_idx,
+            back_at: self.back_at,
+            start: self.start,
+        }
+    }
+}
+
+// 和 std 一样，打印还没有迭代到的 entry
+impl<K, V> fmt::Debug for Iter<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
 }
 
 impl<'a, K, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.map.buckets.get(self.bucket_idx) {
+            match self.bucket() {
                 Some(bucket) => {
                     match bucket.get(self.at) {
                         Some((k, v)) => {
@@ -222,36 +1252,272 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     }
 }
 
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if (self.back_idx, self.back_at) <= (self.bucket_idx, self.at) {
+                break None;
+            }
+            if self.back_at > 0 {
+                self.back_at -= 1;
+                let (k, v) = &self.rotated(self.back_idx)[self.back_at];
+                break Some((k, v));
+            }
+            // 后端退到前一个桶的末尾
+            self.back_idx -= 1;
+            self.back_at = self.rotated(self.back_idx).len();
+        }
+    }
+}
+
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Keys {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+// HashMap::keys_set 返回的视图；交集、差集都是惰性的迭代器，
+// 两个视图的 value 类型和哈希器都可以不同
+pub struct KeySet<'a, K, V, S> {
+    map: &'a HashMap<K, V, S>,
+}
+
+impl<K, V, S> Clone for KeySet<'_, K, V, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, S> Copy for KeySet<'_, K, V, S> {}
+
+impl<'a, K, V, S> KeySet<'a, K, V, S> {
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Keys<'a, K, V> {
+        self.map.keys()
+    }
+}
+
+impl<'a, K, V, S> KeySet<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // 遍历较小的一边，去较大的一边里查
+    pub fn is_disjoint<W, S2>(&self, other: KeySet<'_, K, W, S2>) -> bool
+    where
+        S2: BuildHasher,
+    {
+        if self.len() <= other.len() {
+            self.iter().all(|k| !other.contains(k))
+        } else {
+            other.iter().all(|k| !self.contains(k))
+        }
+    }
+
+    pub fn is_subset<W, S2>(&self, other: KeySet<'_, K, W, S2>) -> bool
+    where
+        S2: BuildHasher,
+    {
+        self.len() <= other.len() && self.iter().all(|k| other.contains(k))
+    }
+
+    pub fn is_superset<W, S2>(&self, other: KeySet<'_, K, W, S2>) -> bool
+    where
+        S2: BuildHasher,
+    {
+        other.is_subset(*self)
+    }
+
+    // 按 self 的迭代顺序给出两边都有的 key
+    pub fn intersection<'b, W, S2>(
+        &self,
+        other: KeySet<'b, K, W, S2>,
+    ) -> impl Iterator<Item = &'a K> + 'b
+    where
+        'a: 'b,
+        S2: BuildHasher,
+    {
+        self.iter().filter(move |k| other.contains(*k))
+    }
+
+    // self 有、other 没有的 key
+    pub fn difference<'b, W, S2>(
+        &self,
+        other: KeySet<'b, K, W, S2>,
+    ) -> impl Iterator<Item = &'a K> + 'b
+    where
+        'a: 'b,
+        S2: BuildHasher,
+    {
+        self.iter().filter(move |k| !other.contains(*k))
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for KeySet<'a, K, V, S> {
+    type Item = &'a K;
+
+    type IntoIter = Keys<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Values {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 // 'a 要求元素的生命周期和Hashmap结构本身绑定
 /*
     let iter = hashmap.iter().next().unwrap();
     drop(hashmap);
     iter....    // iter变成悬垂引用，无法使用
 */
-impl<'a, K, V> IntoIterator for &'a HashMap<K, V> {
+// 同理，Iter 持有 &map、Entry 持有 &mut map，期间任何会 resize 的调用（insert、reserve、remove……）都过不了借用检查，
+// 所以不需要像 C++/Java 那样在运行时记录修改次数（generation）来检测失效的迭代器和 entry
+impl<'a, K, V, S> IntoIterator for &'a HashMap<K, V, S> {
     type Item = (&'a K, &'a V);
 
     type IntoIter = Iter<'a, K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         Self::IntoIter {
-            map: self,
+            buckets: &self.buckets,
+            bucket_idx: 0,
+            at: 0,
+            back_idx: self.buckets.len(),
+            back_at: 0,
+            start: iter_start(&self.buckets),
+        }
+    }
+}
+
+// HashMap::partitions 返回的视图，覆盖一段连续的桶
+pub struct Partition<'a, K, V> {
+    buckets: &'a [Vec<(K, V)>],
+}
+
+impl<'a, K, V> Partition<'a, K, V> {
+    // 需要遍历这一段的桶
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(Vec::is_empty)
+    }
+
+    pub fn iter(&self) -> Iter<'a, K, V> {
+        Iter {
+            buckets: self.buckets,
             bucket_idx: 0,
             at: 0,
+            back_idx: self.buckets.len(),
+            back_at: 0,
+            start: 0,
         }
     }
 }
 
+impl<K, V> Clone for Partition<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Partition<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for Partition<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// 前端从每个桶的尾部 pop，后端从桶的头部取，所以 rev() 正好是正向顺序的反序
 pub struct IntoIter<K, V> {
-    map: HashMap<K, V>,
+    buckets: Vec<Vec<(K, V)>>,
     bucket_idx: usize,
+    back_idx: usize,
+}
+
+impl<K, V> fmt::Debug for IntoIter<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rest = self.buckets[self.bucket_idx.min(self.back_idx)..self.back_idx]
+            .iter()
+            .flat_map(|bucket| bucket.iter().rev())
+            .map(|(k, v)| (k, v));
+        f.debug_list().entries(rest).finish()
+    }
 }
 
 impl<K, V> Iterator for IntoIter<K, V> {
     type Item = (K, V);
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.map.buckets.get_mut(self.bucket_idx) {
+            match self.buckets[..self.back_idx].get_mut(self.bucket_idx) {
                 Some(bucket) => match bucket.pop() {
                     Some(x) => break Some(x),
                     None => {
@@ -265,35 +1531,246 @@ impl<K, V> Iterator for IntoIter<K, V> {
     }
 }
 
-impl<K, V> IntoIterator for HashMap<K, V> {
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.back_idx > self.bucket_idx {
+            let bucket = &mut self.buckets[self.back_idx - 1];
+            if bucket.is_empty() {
+                self.back_idx -= 1;
+            } else {
+                return Some(bucket.remove(0));
+            }
+        }
+        None
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
     type Item = (K, V);
 
     type IntoIter = IntoIter<K, V>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let mut buckets = self.buckets;
+        let start = iter_start(&buckets);
+        buckets.rotate_left(start);
         Self::IntoIter {
-            map: self,
+            back_idx: buckets.len(),
+            buckets,
             bucket_idx: 0,
         }
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for HashMap<K, V>
+// 边遍历边修改：可以原地替换当前 value，也可以直接删掉当前 entry，不需要先收集要删的 key
+pub struct CursorMut<'a, K, V> {
+    buckets: &'a mut Vec<Vec<(K, V)>>,
+    items: &'a mut usize,
+    bucket_idx: usize,
+    at: usize,
+}
+
+impl<K, V> fmt::Debug for CursorMut<'_, K, V>
 where
-    K: Eq + Hash,
+    K: fmt::Debug,
+    V: fmt::Debug,
 {
-    fn from_iter<T>(iter: T) -> Self
-    where
-        T: IntoIterator<Item = (K, V)>,
-    {
-        let mut map = HashMap::new();
-        for (k, v) in iter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let current = self
+            .buckets
+            .get(self.bucket_idx)
+            .and_then(|bucket| bucket.get(self.at));
+        f.debug_tuple("CursorMut").field(&current).finish()
+    }
+}
+
+impl<K, V> CursorMut<'_, K, V> {
+    // 跳过已经走完的桶，停在下一个 entry 上（或者末尾）
+    fn skip_empty(&mut self) {
+        while self
+            .buckets
+            .get(self.bucket_idx)
+            .is_some_and(|bucket| self.at >= bucket.len())
+        {
+            self.bucket_idx += 1;
+            self.at = 0;
+        }
+    }
+
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        let (k, v) = self.buckets.get_mut(self.bucket_idx)?.get_mut(self.at)?;
+        Some((k, v))
+    }
+
+    pub fn move_next(&mut self) {
+        if self.bucket_idx < self.buckets.len() {
+            self.at += 1;
+            self.skip_empty();
+        }
+    }
+
+    // 删掉当前 entry，游标随后指向下一个还没访问过的 entry：
+    // swap_remove 换过来的是桶尾的元素，它本来就排在当前位置之后
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        let bucket = self.buckets.get_mut(self.bucket_idx)?;
+        if self.at >= bucket.len() {
+            return None;
+        }
+        let entry = bucket.swap_remove(self.at);
+        *self.items -= 1;
+        self.skip_empty();
+        Some(entry)
+    }
+
+    pub fn replace_value(&mut self, value: V) -> Option<V> {
+        let (_, v) = self.current()?;
+        Some(std::mem::replace(v, value))
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    // 输入里有重复的 key 时报错，错误里按第一次重复出现的顺序列出每个重复的 key（只列一次）
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, DuplicateKeys<K>>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let iter = iter.into_iter();
+        let mut map = HashMap::with_hasher(S::default());
+        map.reserve(iter.size_hint().0);
+        let mut duplicates = Vec::new();
+        for (k, v) in iter {
+            if map.contains_key(&k) {
+                if !duplicates.contains(&k) {
+                    duplicates.push(k);
+                }
+            } else {
+                map.insert_unique_unchecked(k, v);
+            }
+        }
+        if duplicates.is_empty() {
+            Ok(map)
+        } else {
+            Err(DuplicateKeys { keys: duplicates })
+        }
+    }
+
+    // 从一串 Result 建表，遇到第一个 Err 就停下来返回它；和 collect::<Result<HashMap<_, _>, _>>() 不同，
+    // 会按输入的 size_hint 预先分配好桶。重复的 key 后到覆盖先到
+    pub fn try_from_results<I, E>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<(K, V), E>>,
+    {
+        let iter = iter.into_iter();
+        let mut map = HashMap::with_hasher(S::default());
+        map.reserve(iter.size_hint().0);
+        for entry in iter {
+            let (k, v) = entry?;
             map.insert(k, v);
         }
+        Ok(map)
+    }
+
+    // 重复的 key 交给 combine(key, 已有的值, 新的值) 决定结果：
+    // 先到先得用 |_, old, _| old，后到覆盖用 |_, _, new| new（和 collect 一样），也可以把两个值合并
+    pub fn from_iter_with<I, F>(iter: I, mut combine: F) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        F: FnMut(&K, V, V) -> V,
+    {
+        let iter = iter.into_iter();
+        let mut map = HashMap::with_hasher(S::default());
+        map.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            match map.remove_entry(&k) {
+                Some((ek, old)) => {
+                    let merged = combine(&ek, old, v);
+                    map.insert_unique_unchecked(ek, merged);
+                }
+                None => {
+                    map.insert_unique_unchecked(k, v);
+                }
+            }
+        }
+        map
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = HashMap::with_hasher(S::default());
+        map.extend(iter);
         map
     }
 }
 
+impl<K, V, S> Extend<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+    S: BuildHasher,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.extend(iter.into_iter().map(|(&k, &v)| (k, v)));
+    }
+}
+
+impl<K, V, T> From<std::collections::HashMap<K, V, T>> for HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from(map: std::collections::HashMap<K, V, T>) -> Self {
+        let mut result = HashMap::with_capacity(map.len());
+        // std 的 HashMap 里 key 本来就不重复
+        for (k, v) in map {
+            result.insert_unique_unchecked(k, v);
+        }
+        result
+    }
+}
+
+impl<K, V, S, T> From<HashMap<K, V, S>> for std::collections::HashMap<K, V, T>
+where
+    K: Eq + Hash,
+    T: BuildHasher + Default,
+{
+    fn from(map: HashMap<K, V, S>) -> Self {
+        let mut result = Self::with_capacity_and_hasher(map.len(), T::default());
+        result.extend(map);
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,4 +1818,741 @@ mod tests {
         assert_eq!(4, items);
         // map is moved
     }
+
+    #[test]
+    fn std_conversions() {
+        let std_map: std::collections::HashMap<_, _> = (0..10).map(|i| (i, i * 2)).collect();
+        let mut map = HashMap::from(std_map.clone());
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.get(&3), Some(&6));
+
+        let buckets = map.buckets.len();
+        map.extend_from_std(
+            (5..8)
+                .map(|i| (i, 0))
+                .collect::<std::collections::HashMap<_, _>>(),
+        );
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.get(&5), Some(&0));
+        assert_eq!(map.buckets.len(), buckets);
+
+        let back: std::collections::HashMap<_, _> = map.into();
+        assert_eq!(back.len(), 10);
+        assert_eq!(back[&9], 18);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn debug_validate() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        map.debug_validate();
+        map.retain(|k, _| k % 3 == 0);
+        map.remove(&3);
+        map.debug_validate();
+
+        map.items += 1;
+        let result = std::panic::catch_unwind(|| map.debug_validate());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let empty = HashMap::<u8, u8>::new().stats();
+        assert_eq!(empty.buckets, 0);
+        assert_eq!(empty.max_chain_len, 0);
+
+        // 容量不超过 SMALL_LEN 时只有一个桶
+        let mut map = HashMap::with_capacity(SMALL_LEN + 1);
+        map.buckets[0].push((1, 1));
+        map.buckets[0].push((2, 2));
+        map.buckets[1].push((3, 3));
+        map.items = 3;
+        let stats = map.stats();
+        assert_eq!(stats.buckets, map.buckets.len());
+        assert_eq!(stats.occupied_buckets, 2);
+        assert_eq!(stats.max_chain_len, 2);
+        assert_eq!(stats.mean_chain_len, 3.0 / stats.buckets as f64);
+        assert!(stats.chain_len_variance > 0.0);
+    }
+
+    #[test]
+    fn allocated_bytes() {
+        assert_eq!(HashMap::<u64, u64>::new().allocated_bytes(), 0);
+
+        let mut map = HashMap::new();
+        map.insert(1u64, "foo".to_string());
+        let entry = std::mem::size_of::<(u64, String)>();
+        let bucket = std::mem::size_of::<Vec<(u64, String)>>();
+        let bytes = map.allocated_bytes();
+        assert!(bytes >= entry + bucket);
+        assert_eq!(map.allocated_bytes_with(|_, v| v.capacity()), bytes + 3);
+    }
+
+    #[test]
+    fn or_try_insert_with() {
+        let mut map = HashMap::new();
+        let err = map.entry("foo").or_try_insert_with(|| "x".parse::<i32>());
+        assert!(err.is_err());
+        assert!(map.is_empty());
+        assert_eq!(
+            map.entry("foo").or_try_insert_with(|| "42".parse::<i32>()),
+            Ok(&mut 42)
+        );
+        assert_eq!(
+            map.entry("foo").or_try_insert_with(|| "x".parse::<i32>()),
+            Ok(&mut 42)
+        );
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut map = HashMap::new();
+        *map.get_or_insert_with("foo", || 1) += 10;
+        *map.get_or_insert_with("foo", || unreachable!()) += 10;
+        map.get_or_insert_with("bar", Default::default);
+        assert_eq!(map.get("foo"), Some(&21));
+        assert_eq!(map.get("bar"), Some(&0));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn update() {
+        let mut map = HashMap::new();
+        map.insert("foo".to_string(), 1);
+        assert!(map.update("foo", |v| *v += 1));
+        assert!(!map.update("bar", |v| *v += 1));
+        assert_eq!(map.get("foo"), Some(&2));
+        assert!(!map.contains_key("bar"));
+    }
+
+    #[test]
+    fn merge() {
+        let mut a: HashMap<_, _> = (0..10).map(|i| (i, 1)).collect();
+        let b: HashMap<_, _> = (5..15).map(|i| (i, 10)).collect();
+        a.merge(b, |_, old, new| old + new);
+        assert_eq!(a.len(), 15);
+        assert_eq!(a.get(&0), Some(&1));
+        assert_eq!(a.get(&7), Some(&11));
+        assert_eq!(a.get(&14), Some(&10));
+
+        let mut empty = HashMap::new();
+        empty.merge(HashMap::new(), |_, _: i32, new| new);
+        empty.merge(a, |_, _, new| new);
+        assert_eq!(empty.len(), 15);
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: HashMap<_, _> = (0..6).map(|i| (i, i)).collect();
+        let b: HashMap<_, _> = (4..8).map(|i| (i, i.to_string())).collect();
+
+        let mut common: Vec<_> = a
+            .intersection_with(&b)
+            .map(|(&k, &v, w)| (k, v, w))
+            .collect();
+        common.sort();
+        assert_eq!(common, [(4, 4, &"4".to_string()), (5, 5, &"5".to_string())]);
+
+        let mut only_a: Vec<_> = a.difference(&b).map(|(&k, _)| k).collect();
+        only_a.sort();
+        assert_eq!(only_a, [0, 1, 2, 3]);
+
+        let union: Vec<_> = a.union_with(&b).collect();
+        assert_eq!(union.len(), 8);
+        for (&k, paired) in union {
+            match paired {
+                EitherOrBoth::Left(v) => assert!(k < 4 && *v == k),
+                EitherOrBoth::Both(v, w) => assert!(*v == k && *w == k.to_string()),
+                EitherOrBoth::Right(w) => assert!(k >= 6 && *w == k.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn extend_borrowed() {
+        let other: HashMap<_, _> = (0..10).map(|i| (i, i * 2)).collect();
+        let mut map = HashMap::new();
+        map.insert(0, 100);
+        map.extend(other.iter());
+        map.extend(&other);
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.get(&9), Some(&18));
+    }
+
+    #[test]
+    fn insert_unique_unchecked() {
+        let mut map = HashMap::new();
+        for i in 0..100 {
+            let (&k, v) = map.insert_unique_unchecked(i, i);
+            *v += k;
+        }
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&50), Some(&100));
+        assert_eq!(map.insert(50, 0), Some(100));
+        assert_eq!(map.len(), 100);
+    }
+
+    #[test]
+    fn pop() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let mut popped = Vec::new();
+        while let Some((k, v)) = map.pop() {
+            assert_eq!(k, v);
+            assert!(!map.contains_key(&k));
+            popped.push(k);
+        }
+        popped.sort();
+        assert_eq!(popped, (0..100).collect::<Vec<_>>());
+        assert!(map.is_empty());
+        assert_eq!(map.pop(), None);
+    }
+
+    #[test]
+    fn reseed() {
+        let mut map: HashMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        let before = map.hasher().hash_one(42);
+        map.reseed();
+        #[cfg(not(feature = "deterministic"))]
+        assert_ne!(map.hasher().hash_one(42), before);
+        #[cfg(feature = "deterministic")]
+        assert_eq!(map.hasher().hash_one(42), before);
+        map.rehash_in_place();
+        assert_eq!(map.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), Some(&i));
+        }
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+    }
+
+    #[test]
+    fn iteration_order_is_stable() {
+        let map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let first: Vec<_> = map.iter().collect();
+        let second: Vec<_> = map.iter().collect();
+        assert_eq!(first, second);
+        let mut keys: Vec<_> = first.iter().map(|(&k, _)| k).collect();
+        keys.sort();
+        assert_eq!(keys, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let mut cursor = map.cursor_mut();
+        let mut visited = 0;
+        while let Some((&k, _)) = cursor.current() {
+            visited += 1;
+            if k % 2 == 0 {
+                assert_eq!(cursor.remove_current(), Some((k, k)));
+            } else {
+                assert_eq!(cursor.replace_value(k * 10), Some(k));
+                cursor.move_next();
+            }
+        }
+        assert_eq!(visited, 100);
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(map.len(), 50);
+        assert_eq!(map.get(&3), Some(&30));
+        assert!(!map.contains_key(&4));
+
+        let mut empty: HashMap<i32, i32> = HashMap::new();
+        assert!(empty.cursor_mut().current().is_none());
+    }
+
+    #[test]
+    fn replace_entry_with() {
+        let mut map = HashMap::new();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        let Entry::Occupied(e) = map.entry("a") else {
+            panic!("expected occupied")
+        };
+        let Entry::Occupied(e) = e.replace_entry_with(|_, v| Some(v + 10)) else {
+            panic!("expected occupied")
+        };
+        assert_eq!(e.get(), &11);
+        let Entry::Vacant(e) = e.replace_entry_with(|_, _| None) else {
+            panic!("expected vacant")
+        };
+        assert_eq!(*e.insert(5), 5);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&5));
+
+        let mut interned: HashMap<String, ()> = HashMap::new();
+        let canonical = String::from("key");
+        interned.insert(canonical.clone(), ());
+        let replacement = String::with_capacity(64) + "key";
+        let Entry::Occupied(e) = interned.entry(replacement) else {
+            panic!("expected occupied")
+        };
+        assert_eq!(e.replace_key().capacity(), canonical.capacity());
+        assert!(interned.iter().next().unwrap().0.capacity() >= 64);
+    }
+
+    #[test]
+    fn diff() {
+        let old = hashmap! { "a" => 1, "b" => 2, "c" => 3 };
+        let new = hashmap! { "b" => 2, "c" => 30, "d" => 4 };
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, [(&"d", &4)]);
+        assert_eq!(diff.removed, [(&"a", &1)]);
+        assert_eq!(diff.changed, [(&"c", &3, &30)]);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn group_by() {
+        let words = ["apple", "avocado", "banana", "blueberry", "cherry"];
+        let groups = HashMap::group_by(words.iter().map(|w| (w.as_bytes()[0], *w)));
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups.get(&b'a'), Some(&vec!["apple", "avocado"]));
+        assert_eq!(groups.get(&b'c'), Some(&vec!["cherry"]));
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let ok: HashMap<_, _> = HashMap::try_from_iter([("a", 1), ("b", 2)]).unwrap();
+        assert_eq!(ok.len(), 2);
+        let err =
+            HashMap::<_, _>::try_from_iter([("a", 1), ("b", 2), ("a", 3), ("b", 4), ("a", 5)])
+                .unwrap_err();
+        assert_eq!(err.keys, ["a", "b"]);
+
+        let input = [("a", 1), ("b", 2), ("a", 3)];
+        let first: HashMap<_, _> = HashMap::from_iter_with(input, |_, old, _| old);
+        assert_eq!(first.get("a"), Some(&1));
+        let sum: HashMap<_, _> = HashMap::from_iter_with(input, |_, old, new| old + new);
+        assert_eq!(sum.get("a"), Some(&4));
+        assert_eq!(sum.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn get_many() {
+        let map: HashMap<_, _> = (0..10).map(|i| (i.to_string(), i)).collect();
+        assert_eq!(map.get_many(&["1", "x", "9"]), [Some(&1), None, Some(&9)]);
+        assert!(HashMap::<String, i32>::new().get_many(&["1"])[0].is_none());
+    }
+
+    #[test]
+    fn insert_batch() {
+        let mut map = HashMap::new();
+        map.insert(0, 100);
+        map.insert_batch((0..1000).map(|i| (i, i)));
+        let buckets = map.stats().buckets;
+        map.insert_batch(Vec::new());
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.stats().buckets, buckets);
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+
+        // len() 报成 0 的迭代器，元素也不能丢
+        struct Lying<I>(I);
+        impl<I: Iterator> Iterator for Lying<I> {
+            type Item = I::Item;
+            fn next(&mut self) -> Option<I::Item> {
+                self.0.next()
+            }
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (0, Some(0))
+            }
+        }
+        impl<I: Iterator> ExactSizeIterator for Lying<I> {}
+        let mut map = HashMap::new();
+        map.insert_batch(Lying((0..100).map(|i| (i, i))));
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&99), Some(&99));
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+    }
+
+    #[test]
+    fn debug_impls() {
+        let mut map = HashMap::new();
+        map.insert("a", 1);
+        assert_eq!(
+            format!("{:?}", map.entry("a")),
+            r#"Entry(OccupiedEntry { key: "a", value: 1 })"#
+        );
+        assert_eq!(
+            format!("{:?}", map.entry("b")),
+            r#"Entry(VacantEntry("b"))"#
+        );
+        assert_eq!(format!("{:?}", map.iter()), r#"[("a", 1)]"#);
+        assert_eq!(
+            format!("{:?}", map.cursor_mut()),
+            r#"CursorMut(Some(("a", 1)))"#
+        );
+        let mut iter = map.into_iter();
+        assert_eq!(format!("{iter:?}"), r#"[("a", 1)]"#);
+        iter.next();
+        assert_eq!(format!("{iter:?}"), "[]");
+    }
+
+    #[test]
+    fn clone_iters() {
+        let map: HashMap<_, _> = (0..10).map(|i| (i, i * 2)).collect();
+        let mut iter = map.iter();
+        iter.next();
+        let rest = iter.clone();
+        assert_eq!(iter.count(), 9);
+        assert_eq!(rest.count(), 9);
+
+        let keys = map.keys();
+        assert_eq!(keys.clone().sum::<i32>(), 45);
+        assert_eq!(keys.count(), 10);
+        let values = map.values();
+        assert_eq!(values.clone().sum::<i32>(), 90);
+        assert!(values.zip(map.keys()).all(|(v, k)| *v == k * 2));
+    }
+
+    #[test]
+    fn double_ended() {
+        let map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let forward: Vec<_> = map.iter().collect();
+        let mut backward: Vec<_> = map.iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(map.keys().next_back(), forward.last().map(|(k, _)| *k));
+
+        // 两端交替取，每个 entry 恰好出现一次
+        let mut iter = map.iter();
+        let mut seen = Vec::new();
+        while let Some((&k, _)) = iter.next() {
+            seen.push(k);
+            if let Some((&k, _)) = iter.next_back() {
+                seen.push(k);
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+
+        // clone 出来的表在 randomize-iteration 下起点不同
+        if cfg!(all(feature = "randomize-iteration", debug_assertions)) {
+            return;
+        }
+        let forward: Vec<_> = map.clone().into_iter().collect();
+        let mut backward: Vec<_> = map.into_iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn sorted() {
+        let map: HashMap<_, _> = (0..100).rev().map(|i| (i, i * 2)).collect();
+        let keys: Vec<_> = map.iter_sorted_by_key().map(|(&k, _)| k).collect();
+        assert_eq!(keys, (0..100).collect::<Vec<_>>());
+        let entries = map.into_sorted_vec();
+        assert_eq!(entries[0], (0, 0));
+        assert_eq!(entries[99], (99, 198));
+    }
+
+    #[test]
+    fn replace() {
+        // 只按 id 比较的 key，tag 是随 key 一起存的元数据
+        #[derive(Debug)]
+        struct Tagged {
+            id: u32,
+            tag: &'static str,
+        }
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
+        impl Eq for Tagged {}
+        impl Hash for Tagged {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.id.hash(state);
+            }
+        }
+
+        let mut map = HashMap::new();
+        assert!(map.replace(Tagged { id: 1, tag: "old" }, 10).is_none());
+        let (key, value) = map.replace(Tagged { id: 1, tag: "new" }, 11).unwrap();
+        assert_eq!((key.tag, value), ("old", 10));
+        let (key, value) = map.iter().next().unwrap();
+        assert_eq!((key.tag, *value), ("new", 11));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn content_hash() {
+        let mut a = HashMap::new();
+        let mut b = HashMap::with_capacity(1000);
+        for i in 0..100 {
+            a.insert(i, i * 2);
+            b.insert(99 - i, (99 - i) * 2);
+        }
+        assert_eq!(
+            a.content_hash::<FixedState>(),
+            b.content_hash::<FixedState>()
+        );
+        b.insert(5, 0);
+        assert_ne!(
+            a.content_hash::<FixedState>(),
+            b.content_hash::<FixedState>()
+        );
+        assert_ne!(
+            HashMap::<u32, u32>::new().content_hash::<FixedState>(),
+            a.content_hash::<FixedState>()
+        );
+    }
+
+    #[test]
+    fn partitions() {
+        let map: HashMap<u64, u64> = (0..1000).map(|i| (i, i)).collect();
+        let parts = map.partitions(3);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts.iter().map(Partition::len).sum::<usize>(), 1000);
+        let sum: u64 = std::thread::scope(|scope| {
+            let handles: Vec<_> = parts
+                .into_iter()
+                .map(|part| scope.spawn(move || part.iter().map(|(_, v)| v).sum::<u64>()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(sum, (0..1000).sum::<u64>());
+
+        // 分区数比桶数多时，多出来的分区是空的
+        let small: HashMap<u64, u64> = HashMap::new();
+        assert!(small.partitions(4).iter().all(|p| p.is_empty()));
+    }
+
+    #[test]
+    fn split_off_if() {
+        let mut map: HashMap<u32, String> = (0..100).map(|i| (i, i.to_string())).collect();
+        let odd = map.split_off_if(|k, _| k % 2 == 1);
+        assert_eq!(map.len(), 50);
+        assert_eq!(odd.len(), 50);
+        assert!(map.keys().all(|k| k % 2 == 0));
+        assert_eq!(odd.get(&7).map(String::as_str), Some("7"));
+        assert!(map.split_off_if(|_, _| false).is_empty());
+        assert_eq!(map.len(), 50);
+    }
+
+    #[test]
+    fn retain_map() {
+        let mut map: HashMap<u32, String> = (0..10).map(|i| (i, i.to_string())).collect();
+        map.retain_map(|k, mut v| {
+            (k % 3 != 0).then(|| {
+                v.push('!');
+                v
+            })
+        });
+        assert_eq!(map.len(), 6);
+        assert_eq!(map.get(&1).map(String::as_str), Some("1!"));
+        assert!(!map.contains_key(&3));
+
+        let mut map: HashMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.retain_map(|&k, v| {
+                assert_ne!(k, 50);
+                Some(v)
+            })
+        }));
+        assert!(result.is_err());
+        assert!(map.len() < 100);
+        assert_eq!(map.len(), map.iter().count());
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+    }
+
+    #[test]
+    fn map_keys_values() {
+        let map: HashMap<u32, u32> = (0..100).map(|i| (i, i * 2)).collect();
+        let strings = map.into_map_values(|k, v| format!("{k}:{v}"));
+        assert_eq!(strings.len(), 100);
+        assert_eq!(strings.get(&7).map(String::as_str), Some("7:14"));
+
+        let shifted = strings.map_keys(|k| u64::from(k) + 1000);
+        assert_eq!(shifted.len(), 100);
+        assert_eq!(shifted.get(&1007).map(String::as_str), Some("7:14"));
+        assert!(!shifted.contains_key(&7));
+
+        let merged = shifted.map_keys(|k| k % 10);
+        assert_eq!(merged.len(), 10);
+    }
+
+    #[test]
+    fn try_from_results() {
+        let parse = |line: &str| -> Result<(String, u32), std::num::ParseIntError> {
+            let (k, v) = line.split_once('=').unwrap();
+            Ok((k.to_string(), v.parse()?))
+        };
+        let map: HashMap<_, _> =
+            HashMap::try_from_results(["a=1", "b=2", "a=3"].into_iter().map(parse)).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&3));
+
+        let mut consumed = 0;
+        let err = HashMap::<String, u32>::try_from_results(
+            ["a=1", "b=x", "c=3"]
+                .into_iter()
+                .inspect(|_| consumed += 1)
+                .map(parse),
+        );
+        assert!(err.is_err());
+        assert_eq!(consumed, 2);
+    }
+
+    #[test]
+    fn contains_value_find() {
+        let map: HashMap<&str, u32> = [("a", 1), ("b", 20), ("c", 3)].into_iter().collect();
+        assert!(map.contains_value(&20));
+        assert!(!map.contains_value(&2));
+        assert_eq!(map.find(|_, v| *v > 10), Some((&"b", &20)));
+        assert_eq!(map.find(|k, _| k.len() > 1), None);
+    }
+
+    #[test]
+    fn keys_set() {
+        let granted: HashMap<&str, u8> = [("read", 1), ("write", 2)].into_iter().collect();
+        let required: HashMap<&str, ()> = [("read", ())].into_iter().collect();
+        let other: HashMap<&str, ()> = [("admin", ())].into_iter().collect();
+
+        assert!(required.keys_set().is_subset(granted.keys_set()));
+        assert!(granted.keys_set().is_superset(required.keys_set()));
+        assert!(!granted.keys_set().is_subset(required.keys_set()));
+        assert!(granted.keys_set().is_disjoint(other.keys_set()));
+        assert!(!granted.keys_set().is_disjoint(required.keys_set()));
+
+        let common: Vec<_> = granted
+            .keys_set()
+            .intersection(required.keys_set())
+            .collect();
+        assert_eq!(common, [&"read"]);
+        let extra: Vec<_> = granted.keys_set().difference(required.keys_set()).collect();
+        assert_eq!(extra, [&"write"]);
+        assert!(granted.keys_set().contains("write"));
+        assert_eq!(granted.keys_set().into_iter().count(), 2);
+    }
+
+    #[test]
+    fn growth_policy() {
+        let mut capped = HashMap::new();
+        capped.set_growth_policy(growth::Capped {
+            inner: growth::Doubling,
+            max_buckets: 64,
+        });
+        for i in 0..1000 {
+            capped.insert(i, i);
+        }
+        assert_eq!(capped.buckets.len(), 64);
+        assert!((0..1000).all(|i| capped.get(&i) == Some(&i)));
+
+        // 按 size_hint / len 预分配的路径也不能越过上限
+        let mut collected = HashMap::new();
+        collected.set_growth_policy(growth::Capped {
+            inner: growth::Doubling,
+            max_buckets: 64,
+        });
+        collected.extend((0..1000).map(|i| (i, i)));
+        assert_eq!(collected.buckets.len(), 64);
+        collected.reserve(1_000_000);
+        collected.insert_batch((1000..2000).map(|i| (i, i)));
+        collected.merge(capped.clone(), |_, a, b| a + b);
+        assert_eq!(collected.buckets.len(), 64);
+        assert_eq!(collected.len(), 2000);
+        assert_eq!(collected.get(&7), Some(&14));
+        let collected = collected.map_keys(|k| k + 1);
+        assert_eq!(collected.buckets.len(), 64);
+
+        let mut fixed = HashMap::new();
+        fixed.set_growth_policy(growth::FixedIncrement(10));
+        for i in 0..100 {
+            fixed.insert(i, i);
+        }
+        assert_eq!(fixed.buckets.len() % 10, 1);
+        assert_eq!(fixed.clone().len(), 100);
+    }
+
+    #[test]
+    fn small_no_hash() {
+        // 哈希一次就计数一次的 key
+        struct Counted<'a>(u32, &'a std::cell::Cell<usize>);
+        impl PartialEq for Counted<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Counted<'_> {}
+        impl Hash for Counted<'_> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.1.set(self.1.get() + 1);
+                self.0.hash(state);
+            }
+        }
+
+        let hashes = std::cell::Cell::new(0);
+        let mut map = HashMap::new();
+        for i in 0..SMALL_LEN as u32 {
+            map.insert(Counted(i, &hashes), i);
+        }
+        assert!(map.contains_key(&Counted(3, &hashes)));
+        map.remove(&Counted(0, &hashes));
+        assert_eq!(hashes.get(), 0);
+        assert_eq!(map.buckets.len(), 1);
+
+        for i in 100..200 {
+            map.insert(Counted(i, &hashes), i);
+        }
+        assert!(hashes.get() > 0);
+        assert!(map.buckets.len() > 1);
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+        assert_eq!(map.get(&Counted(150, &hashes)), Some(&150));
+    }
+
+    #[test]
+    fn zero_sized_values() {
+        assert_eq!(std::mem::size_of::<(u64, ())>(), std::mem::size_of::<u64>());
+        let set: HashMap<u64, ()> = (0..100).map(|i| (i, ())).collect();
+        let keys: HashMap<u64, u64> = (0..100).map(|i| (i, 0)).collect();
+        assert_eq!(set.buckets.len(), keys.buckets.len());
+        assert!(set.allocated_bytes() < keys.allocated_bytes());
+        let chains: usize = set.buckets.iter().map(|b| b.capacity() * 8).sum();
+        assert_eq!(
+            set.allocated_bytes(),
+            set.buckets.capacity() * std::mem::size_of::<Vec<(u64, ())>>() + chains
+        );
+    }
+
+    #[test]
+    fn first_entry() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i, i)).collect();
+        let mut entry = map.first_entry().unwrap();
+        let key = *entry.key();
+        assert_eq!(entry.insert(1000), key);
+        *entry.get_mut() += 1;
+        assert_eq!(map.get(&key), Some(&1001));
+        assert_eq!(map.iter().next(), Some((&key, &1001)));
+
+        let mut drained = 0;
+        while let Some(entry) = map.first_entry() {
+            entry.remove();
+            drained += 1;
+        }
+        assert_eq!(drained, 100);
+        assert!(map.is_empty());
+        #[cfg(debug_assertions)]
+        map.debug_validate();
+    }
+
+    #[test]
+    fn insert_entry() {
+        let mut map = HashMap::new();
+        let entry = map.entry("a").insert_entry(1);
+        assert_eq!((entry.key(), entry.get()), (&"a", &1));
+        *entry.into_mut() += 1;
+        let mut entry = map.entry("a").insert_entry(10);
+        *entry.get_mut() *= 2;
+        assert_eq!(entry.remove_entry(), ("a", 20));
+        map.entry("b").insert_entry(2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("b"), Some(&2));
+    }
 }
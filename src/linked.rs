@@ -0,0 +1,340 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{make_hash, INITIAL_NBUCKETS};
+
+struct Node<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+// 双向链表的节点放在 nodes 里（空位由 free 复用），链表用下标串起来；indices 是旁路的哈希索引
+pub struct LinkedHashMap<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    indices: Vec<Vec<usize>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
+    // 为 true 时，get / insert 会把访问到的 entry 移到链表尾部
+    access_order: bool,
+}
+
+impl<K, V> LinkedHashMap<K, V> {
+    pub fn new() -> Self {
+        LinkedHashMap {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            indices: Vec::new(),
+            head: None,
+            tail: None,
+            len: 0,
+            access_order: false,
+        }
+    }
+
+    pub fn with_access_order() -> Self {
+        LinkedHashMap {
+            access_order: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn set_access_order(&mut self, access_order: bool) {
+        self.access_order = access_order;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<(&K, &V)> {
+        let node = self.node(self.head?);
+        Some((&node.key, &node.value))
+    }
+
+    pub fn back(&self) -> Option<(&K, &V)> {
+        let node = self.node(self.tail?);
+        Some((&node.key, &node.value))
+    }
+
+    pub fn pop_front(&mut self) -> Option<(K, V)> {
+        let idx = self.head?;
+        Some(self.remove_node(idx))
+    }
+
+    pub fn pop_back(&mut self) -> Option<(K, V)> {
+        let idx = self.tail?;
+        Some(self.remove_node(idx))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            map: self,
+            at: self.head,
+        }
+    }
+
+    fn node(&self, idx: usize) -> &Node<K, V> {
+        self.nodes[idx].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut Node<K, V> {
+        self.nodes[idx].as_mut().unwrap()
+    }
+
+    fn bucket_of(&self, hash: u64) -> usize {
+        (hash % self.indices.len() as u64) as usize
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.indices.len() {
+            0 => INITIAL_NBUCKETS,
+            n => 2 * n,
+        };
+        self.indices = (0..target_size).map(|_| Vec::new()).collect();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if let Some(node) = node {
+                let bucket = (node.hash % target_size as u64) as usize;
+                self.indices[bucket].push(idx);
+            }
+        }
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = {
+            let node = self.node(idx);
+            (node.prev, node.next)
+        };
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn link_back(&mut self, idx: usize) {
+        let tail = self.tail;
+        {
+            let node = self.node_mut(idx);
+            node.prev = tail;
+            node.next = None;
+        }
+        match tail {
+            Some(tail) => self.node_mut(tail).next = Some(idx),
+            None => self.head = Some(idx),
+        }
+        self.tail = Some(idx);
+    }
+
+    fn move_to_back(&mut self, idx: usize) {
+        if self.tail != Some(idx) {
+            self.unlink(idx);
+            self.link_back(idx);
+        }
+    }
+
+    fn remove_node(&mut self, idx: usize) -> (K, V) {
+        self.unlink(idx);
+        let node = self.nodes[idx].take().unwrap();
+        let bucket = self.bucket_of(node.hash);
+        self.indices[bucket].retain(|&i| i != idx);
+        self.free.push(idx);
+        self.len -= 1;
+        (node.key, node.value)
+    }
+}
+
+impl<K, V> Default for LinkedHashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> LinkedHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.indices.is_empty() {
+            return None;
+        }
+        let hash = make_hash(key);
+        self.indices[self.bucket_of(hash)]
+            .iter()
+            .copied()
+            .find(|&idx| {
+                let node = self.node(idx);
+                node.hash == hash && node.key.borrow() == key
+            })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(idx) = self.find(&key) {
+            if self.access_order {
+                self.move_to_back(idx);
+            }
+            return Some(std::mem::replace(&mut self.node_mut(idx).value, value));
+        }
+        if self.indices.is_empty() || self.len > 3 * self.indices.len() / 4 {
+            self.resize();
+        }
+        let hash = make_hash(&key);
+        let node = Node {
+            hash,
+            key,
+            value,
+            prev: None,
+            next: None,
+        };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        let bucket = self.bucket_of(hash);
+        self.indices[bucket].push(idx);
+        self.link_back(idx);
+        self.len += 1;
+        None
+    }
+
+    // access order 模式下会更新访问顺序，所以需要 &mut self；只读查询用 peek
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        if self.access_order {
+            self.move_to_back(idx);
+        }
+        Some(&mut self.node_mut(idx).value)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).map(|idx| &self.node(idx).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(key)?;
+        Some(self.remove_node(idx).1)
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    map: &'a LinkedHashMap<K, V>,
+    at: Option<usize>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.map.node(self.at?);
+        self.at = node.next;
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a LinkedHashMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for LinkedHashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = LinkedHashMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut map: LinkedHashMap<_, _> = (0..5).map(|i| (i, i)).collect();
+        map.insert(2, 20);
+        assert_eq!(map.get(&0), Some(&0));
+        assert_eq!(map.remove(&3), Some(3));
+        let keys: Vec<_> = map.iter().map(|(&k, _)| k).collect();
+        assert_eq!(keys, [0, 1, 2, 4]);
+        assert_eq!(map.pop_front(), Some((0, 0)));
+        assert_eq!(map.pop_back(), Some((4, 4)));
+        assert_eq!(map.len(), 2);
+        map.insert(5, 5);
+        assert_eq!(map.back(), Some((&5, &5)));
+    }
+
+    #[test]
+    fn access_order() {
+        let mut map = LinkedHashMap::with_access_order();
+        for i in 0..5 {
+            map.insert(i, i);
+        }
+        map.get(&1);
+        map.insert(0, 10);
+        assert_eq!(map.peek(&2), Some(&2));
+        let keys: Vec<_> = map.iter().map(|(&k, _)| k).collect();
+        assert_eq!(keys, [2, 3, 4, 1, 0]);
+        // 最久未访问的在链表头部
+        assert_eq!(map.pop_front(), Some((2, 2)));
+    }
+}
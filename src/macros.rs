@@ -0,0 +1,37 @@
+// hashmap! { "a" => 1, "b" => 2 }，按字面量的个数预先分配容量
+#[macro_export]
+macro_rules! hashmap {
+    (@single $($x:tt)*) => (());
+    (@count $($rest:expr),*) => (<[()]>::len(&[$($crate::hashmap!(@single $rest)),*]));
+
+    () => { $crate::HashMap::new() };
+    ($($key:expr => $value:expr,)+) => { $crate::hashmap!($($key => $value),+) };
+    ($($key:expr => $value:expr),*) => {{
+        let capacity = $crate::hashmap!(@count $($key),*);
+        let mut map = $crate::HashMap::with_capacity(capacity);
+        $(
+            let _ = map.insert($key, $value);
+        )*
+        map
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HashMap;
+
+    #[test]
+    fn hashmap() {
+        let map = hashmap! {
+            "foo" => 1,
+            "bar" => 2,
+            "foo" => 3,
+        };
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("foo"), Some(&3));
+
+        let empty: HashMap<u8, u8> = hashmap! {};
+        assert!(empty.is_empty());
+        assert_eq!(hashmap! { 1 => 'a' }.get(&1), Some(&'a'));
+    }
+}
@@ -3,6 +3,7 @@ fn main() {
     test2();
 }
 
+#[allow(dead_code)]
 fn test1() {
     // Type inference lets us omit an explicit type signature (which
     // would be `HashMap<String, String>` in this example).
@@ -82,6 +83,7 @@ fn test2() {
     *stat += random_stat_buff();
     assert_eq!(player_stats.len(), 3);
 }
+#[allow(dead_code)]
 fn test3() {
     let _timber_resources: HashMap<&str, i32> = [("Norway", 100), ("Denmark", 50), ("Iceland", 10)]
         .iter()
@@ -0,0 +1,197 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    marker::PhantomData,
+    path::Path,
+};
+
+use memmap2::Mmap;
+
+use crate::{hash::fnv1a, snapshot::write_atomically, Codec, FrozenMap};
+
+// 文件布局（整数都是小端序，所有位置都是偏移量而不是指针，可以直接在映射出来的字节上查找）：
+//   MAGIC | 桶的个数 n（u64，2 的幂）| entry 个数 m（u64）
+//   | 桶表：n + 1 个 u64，第 i 个桶是第 offsets[i]..offsets[i + 1] 个 entry
+//   | entry 表：m + 1 个 u64，第 j 个 entry 的数据在 data[pos[j]..pos[j + 1]]
+//   | data：每个 entry 是 key 的长度（u32）、key 的字节、value 的字节
+// 桶按编码后 key 字节的 FNV-1a 哈希划分，和进程、Rust 版本都无关，文件可以跨版本读取
+const MAGIC: &[u8; 8] = b"RHMMAP01";
+const HEADER: usize = 24;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl<K, V> FrozenMap<K, V>
+where
+    K: Codec,
+    V: Codec,
+{
+    pub fn write_mmap(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let nbuckets = self.len().next_power_of_two() as u64;
+        let mut entries: Vec<_> = self
+            .iter()
+            .map(|(k, v)| {
+                let mut bytes = Vec::new();
+                k.encode(&mut bytes);
+                let key_len = u32::try_from(bytes.len())
+                    .map_err(|_| invalid_data("key longer than 4 GiB"))?;
+                let hash = fnv1a(&bytes);
+                bytes.splice(0..0, key_len.to_le_bytes());
+                v.encode(&mut bytes);
+                Ok((hash & (nbuckets - 1), bytes))
+            })
+            .collect::<io::Result<_>>()?;
+        entries.sort_by_key(|(bucket, _)| *bucket);
+
+        let mut offsets = vec![0u64; nbuckets as usize + 1];
+        for (bucket, _) in &entries {
+            offsets[*bucket as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+
+        write_atomically(path.as_ref(), |writer| {
+            writer.write_all(MAGIC)?;
+            writer.write_all(&nbuckets.to_le_bytes())?;
+            writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+            for offset in offsets {
+                writer.write_all(&offset.to_le_bytes())?;
+            }
+            let mut pos = 0u64;
+            writer.write_all(&pos.to_le_bytes())?;
+            for (_, bytes) in &entries {
+                pos += bytes.len() as u64;
+                writer.write_all(&pos.to_le_bytes())?;
+            }
+            for (_, bytes) in &entries {
+                writer.write_all(bytes)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// write_mmap 写出的文件的只读视图；open 只检查头部和各个表的大小，
+// 查找时不反序列化整张表，只解码命中的那个 value
+pub struct MmapMap<K, V> {
+    mmap: Mmap,
+    nbuckets: usize,
+    len: usize,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> MmapMap<K, V> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: 映射期间文件被其他进程截断或修改属于调用方的约定之外；
+        // 下面所有访问都做了边界检查，内容被改只会得到错误的结果或 InvalidData
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.len() < HEADER || &mmap[..8] != MAGIC {
+            return Err(invalid_data("not a mmap map file"));
+        }
+        let nbuckets = u64::from_le_bytes(mmap[8..16].try_into().unwrap());
+        let len = u64::from_le_bytes(mmap[16..24].try_into().unwrap());
+        let tables = nbuckets
+            .checked_add(len)
+            .and_then(|n| n.checked_add(2))
+            .and_then(|n| n.checked_mul(8))
+            .filter(|&n| n <= (mmap.len() - HEADER) as u64);
+        if !nbuckets.is_power_of_two() || tables.is_none() {
+            return Err(invalid_data("corrupt mmap map header"));
+        }
+        Ok(MmapMap {
+            mmap,
+            nbuckets: nbuckets as usize,
+            len: len as usize,
+            marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn read_u64(&self, at: usize) -> io::Result<usize> {
+        self.mmap
+            .get(at..at + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()) as usize)
+            .ok_or_else(|| invalid_data("offset out of bounds"))
+    }
+
+    // 第 idx 个 entry 的 (key, value) 字节
+    fn entry(&self, idx: usize) -> io::Result<(&[u8], &[u8])> {
+        let positions = HEADER + 8 * (self.nbuckets + 1);
+        let data = positions + 8 * (self.len + 1);
+        let start = self.read_u64(positions + 8 * idx)?;
+        let end = self.read_u64(positions + 8 * (idx + 1))?;
+        let bytes = data
+            .checked_add(start)
+            .zip(data.checked_add(end))
+            .and_then(|(start, end)| self.mmap.get(start..end))
+            .filter(|bytes| bytes.len() >= 4)
+            .ok_or_else(|| invalid_data("entry out of bounds"))?;
+        let key_len = u32::from_le_bytes(bytes[..4].try_into().unwrap()) as usize;
+        if bytes.len() - 4 < key_len {
+            return Err(invalid_data("key out of bounds"));
+        }
+        Ok(bytes[4..].split_at(key_len))
+    }
+
+    // 按编码后的 key 查找，返回 value 编码后的字节，不做任何拷贝
+    pub fn get_bytes(&self, key: &[u8]) -> io::Result<Option<&[u8]>> {
+        let bucket = (fnv1a(key) & (self.nbuckets as u64 - 1)) as usize;
+        let start = self.read_u64(HEADER + 8 * bucket)?;
+        let end = self.read_u64(HEADER + 8 * (bucket + 1))?;
+        for idx in start..end.min(self.len) {
+            let (ekey, evalue) = self.entry(idx)?;
+            if ekey == key {
+                return Ok(Some(evalue));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<K, V> MmapMap<K, V>
+where
+    K: Codec,
+    V: Codec,
+{
+    pub fn get(&self, key: &K) -> io::Result<Option<V>> {
+        let mut bytes = Vec::new();
+        key.encode(&mut bytes);
+        self.get_bytes(&bytes)?.map(V::decode).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_open() {
+        let path = std::env::temp_dir().join(format!("rust-hashmap-{}.mmap", std::process::id()));
+        let frozen: FrozenMap<String, u64> = (0..1000).map(|i| (i.to_string(), i)).collect();
+        frozen.write_mmap(&path).unwrap();
+        let map = MmapMap::<String, u64>::open(&path).unwrap();
+        assert_eq!(map.len(), 1000);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i.to_string()).unwrap(), Some(i));
+        }
+        assert_eq!(map.get(&"1000".to_string()).unwrap(), None);
+        assert_eq!(map.get_bytes(b"7").unwrap(), Some(&7u64.to_le_bytes()[..]));
+
+        let empty: FrozenMap<String, u64> = FrozenMap::from_iter([]);
+        empty.write_mmap(&path).unwrap();
+        let map = MmapMap::<String, u64>::open(&path).unwrap();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&"0".to_string()).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{Entry, HashMap};
+
+// 一个 key 对应一组 value；remove 删空的组会连同 key 一起删掉
+pub struct MultiMap<K, V> {
+    map: HashMap<K, Vec<V>>,
+}
+
+impl<K, V> MultiMap<K, V> {
+    pub fn new() -> Self {
+        MultiMap {
+            map: HashMap::new(),
+        }
+    }
+
+    // key 的个数
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            groups: self.map.iter(),
+            current: None,
+        }
+    }
+
+    pub fn iter_all(&self) -> crate::Iter<'_, K, Vec<V>> {
+        self.map.iter()
+    }
+}
+
+impl<K, V> Default for MultiMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> MultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+    }
+
+    // 按组操作，例如 entry(k).or_default().extend(values)
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, Vec<V>> {
+        self.map.entry(key)
+    }
+
+    // 返回第一个 value
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).and_then(|values| values.first())
+    }
+
+    pub fn get_vec<Q>(&self, key: &Q) -> Option<&Vec<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    // 删除 key 下的一个等于 value 的值，返回是否删除成功
+    pub fn remove<Q>(&mut self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: PartialEq,
+    {
+        let Some(values) = self.map.get_mut(key) else {
+            return false;
+        };
+        let Some(pos) = values.iter().position(|v| v == value) else {
+            return false;
+        };
+        values.remove(pos);
+        if values.is_empty() {
+            self.map.remove(key);
+        }
+        true
+    }
+
+    pub fn remove_all<Q>(&mut self, key: &Q) -> Option<Vec<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key)
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    groups: crate::Iter<'a, K, Vec<V>>,
+    current: Option<(&'a K, std::slice::Iter<'a, V>)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.current {
+                if let Some(value) = values.next() {
+                    break Some((*key, value));
+                }
+            }
+            let (key, values) = self.groups.next()?;
+            self.current = Some((key, values.iter()));
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a MultiMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MultiMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        MultiMap {
+            map: HashMap::group_by(iter),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert() {
+        let mut map = MultiMap::new();
+        map.insert("foo", 1);
+        map.insert("foo", 2);
+        map.insert("bar", 3);
+        map.entry("bar").or_default().extend([4, 5]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"foo"), Some(&1));
+        assert_eq!(map.get_vec(&"bar"), Some(&vec![3, 4, 5]));
+        assert_eq!(map.iter().count(), 5);
+        assert_eq!(map.iter().map(|(_, &v)| v).sum::<i32>(), 15);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map: MultiMap<_, _> = [("foo", 1), ("foo", 2), ("bar", 3)].into_iter().collect();
+        assert!(map.remove(&"foo", &1));
+        assert!(!map.remove(&"foo", &1));
+        assert!(map.remove(&"bar", &3));
+        assert!(!map.contains_key(&"bar"));
+        assert_eq!(map.remove_all(&"foo"), Some(vec![2]));
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    borrow::Borrow,
+    hash::Hash,
+    sync::{PoisonError, RwLock},
+};
+
+use crate::persistent;
+
+// 某个版本的只读视图；底层是持久化 HAMT，拿到之后不受后续写入影响，clone 也是 O(1)
+pub struct Snapshot<K, V> {
+    version: u64,
+    map: persistent::HashMap<K, V>,
+}
+
+impl<K, V> Snapshot<K, V> {
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> persistent::Iter<'_, K, V> {
+        self.map.iter()
+    }
+}
+
+impl<K, V> Snapshot<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+}
+
+impl<K, V> Clone for Snapshot<K, V> {
+    fn clone(&self) -> Self {
+        Snapshot {
+            version: self.version,
+            map: self.map.clone(),
+        }
+    }
+}
+
+// 多版本 map：每次写入生成一个新版本，读者用 snapshot() 固定住某个版本后随便读，
+// 写者不会等读者，读者也只在取快照的一瞬间拿读锁；适合读多写少的配置数据
+pub struct VersionedMap<K, V> {
+    current: RwLock<Snapshot<K, V>>,
+}
+
+impl<K, V> VersionedMap<K, V> {
+    pub fn new() -> Self {
+        VersionedMap {
+            current: RwLock::new(Snapshot {
+                version: 0,
+                map: persistent::HashMap::new(),
+            }),
+        }
+    }
+
+    // 新版本是整体替换进去的，持锁期间 panic 也不会留下改了一半的状态，所以可以忽略 poison
+    pub fn snapshot(&self) -> Snapshot<K, V> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .version
+    }
+
+    // 基于最新版本生成下一个版本，f 里的多次修改作为一个版本整体可见；返回新的版本号
+    pub fn update<F>(&self, f: F) -> u64
+    where
+        F: FnOnce(&persistent::HashMap<K, V>) -> persistent::HashMap<K, V>,
+    {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let map = f(&current.map);
+        *current = Snapshot {
+            version: current.version + 1,
+            map,
+        };
+        current.version
+    }
+}
+
+impl<K, V> Default for VersionedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> VersionedMap<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn insert(&self, key: K, value: V) -> u64 {
+        self.update(|map| map.insert(key, value))
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> u64
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.update(|map| map.remove(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn snapshot_isolation() {
+        let map = VersionedMap::new();
+        map.insert("a", 1);
+        let before = map.snapshot();
+        assert_eq!(map.insert("a", 2), 2);
+        map.update(|m| m.insert("b", 3).remove("a"));
+
+        assert_eq!(before.version(), 1);
+        assert_eq!(before.get("a"), Some(&1));
+        assert_eq!(before.len(), 1);
+        let after = map.snapshot();
+        assert_eq!(after.version(), 3);
+        assert_eq!(after.get("a"), None);
+        assert_eq!(after.get("b"), Some(&3));
+    }
+
+    #[test]
+    fn concurrent_readers() {
+        let map = VersionedMap::new();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..1000 {
+                    map.insert(i, i);
+                }
+            });
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        let snapshot = map.snapshot();
+                        assert_eq!(snapshot.len() as u64, snapshot.version());
+                        assert_eq!(snapshot.iter().count(), snapshot.len());
+                    }
+                });
+            }
+        });
+        assert_eq!(map.snapshot().len(), 1000);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    borrow::Borrow,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::HashMap;
+
+// 规定 key 怎样被哈希和比较：hash 和 eq 必须一致（eq 的两个 key 哈希也相同），
+// 这样 key 按原样存储，不需要额外保存一份规范化之后的副本
+pub trait KeyNormalizer {
+    type Key: ?Sized;
+
+    fn hash<H: Hasher>(key: &Self::Key, state: &mut H);
+
+    fn eq(a: &Self::Key, b: &Self::Key) -> bool;
+}
+
+// 只忽略 ASCII 字母的大小写，例如 HTTP 头部名
+pub struct AsciiCaseInsensitive;
+
+impl KeyNormalizer for AsciiCaseInsensitive {
+    type Key = str;
+
+    fn hash<H: Hasher>(key: &str, state: &mut H) {
+        for byte in key.bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+        // 和 str 的 Hash 一样写一个结束标记，避免拼接起来相同的 key 序列冲突
+        state.write_u8(0xff);
+    }
+
+    fn eq(a: &str, b: &str) -> bool {
+        a.eq_ignore_ascii_case(b)
+    }
+}
+
+// 表里实际存的 key，Hash / Eq 都转给 N
+struct Normalized<K, N> {
+    key: K,
+    marker: PhantomData<N>,
+}
+
+// 查找时不能为了借用而构造一个 Normalized<K>，所以把存储的 key 和查找用的 &N::Key
+// 都借用成同一个 trait object，在它上面按 N 实现 Hash / Eq
+trait View<N: KeyNormalizer> {
+    fn view(&self) -> &N::Key;
+}
+
+impl<K, N> View<N> for Normalized<K, N>
+where
+    K: Borrow<N::Key>,
+    N: KeyNormalizer,
+{
+    fn view(&self) -> &N::Key {
+        self.key.borrow()
+    }
+}
+
+struct Probe<'a, Q: ?Sized>(&'a Q);
+
+impl<N> View<N> for Probe<'_, N::Key>
+where
+    N: KeyNormalizer,
+{
+    fn view(&self) -> &N::Key {
+        self.0
+    }
+}
+
+impl<N: KeyNormalizer> Hash for dyn View<N> + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        N::hash(self.view(), state);
+    }
+}
+
+impl<N: KeyNormalizer> PartialEq for dyn View<N> + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        N::eq(self.view(), other.view())
+    }
+}
+
+impl<N: KeyNormalizer> Eq for dyn View<N> + '_ {}
+
+impl<'a, K, N> Borrow<dyn View<N> + 'a> for Normalized<K, N>
+where
+    K: Borrow<N::Key> + 'a,
+    N: KeyNormalizer + 'a,
+{
+    fn borrow(&self) -> &(dyn View<N> + 'a) {
+        self
+    }
+}
+
+impl<K, N> Hash for Normalized<K, N>
+where
+    K: Borrow<N::Key>,
+    N: KeyNormalizer,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        N::hash(self.key.borrow(), state);
+    }
+}
+
+impl<K, N> PartialEq for Normalized<K, N>
+where
+    K: Borrow<N::Key>,
+    N: KeyNormalizer,
+{
+    fn eq(&self, other: &Self) -> bool {
+        N::eq(self.key.borrow(), other.key.borrow())
+    }
+}
+
+impl<K, N> Eq for Normalized<K, N>
+where
+    K: Borrow<N::Key>,
+    N: KeyNormalizer,
+{
+}
+
+// key 经过 N 规范化之后再哈希和比较；保存的是第一次插入时的原始 key
+pub struct NormalizedMap<K, V, N> {
+    map: HashMap<Normalized<K, N>, V>,
+}
+
+pub type CaseInsensitiveMap<V> = NormalizedMap<String, V, AsciiCaseInsensitive>;
+
+impl<K, V, N> NormalizedMap<K, V, N> {
+    pub fn new() -> Self {
+        NormalizedMap {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter().map(|(k, v)| (&k.key, v))
+    }
+}
+
+impl<K, V, N> Default for NormalizedMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, N> NormalizedMap<K, V, N>
+where
+    K: Borrow<N::Key>,
+    N: KeyNormalizer,
+{
+    // 已经存在等价的 key 时只替换 value，保留原来的 key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.map.insert(
+            Normalized {
+                key,
+                marker: PhantomData,
+            },
+            value,
+        )
+    }
+
+    pub fn get(&self, key: &N::Key) -> Option<&V> {
+        self.map.get(&Probe(key) as &dyn View<N>)
+    }
+
+    pub fn get_mut(&mut self, key: &N::Key) -> Option<&mut V> {
+        self.map.get_mut(&Probe(key) as &dyn View<N>)
+    }
+
+    pub fn contains_key(&self, key: &N::Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn remove(&mut self, key: &N::Key) -> Option<V> {
+        self.map.remove(&Probe(key) as &dyn View<N>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_insensitive() {
+        let mut headers = CaseInsensitiveMap::new();
+        headers.insert("Content-Type".to_string(), "text/html");
+        assert_eq!(
+            headers.insert("content-type".to_string(), "text/plain"),
+            Some("text/html")
+        );
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get("CONTENT-TYPE"), Some(&"text/plain"));
+        assert_eq!(headers.iter().next().unwrap().0, "Content-Type");
+        assert!(!headers.contains_key("Content-Length"));
+        assert_eq!(headers.remove("content-TYPE"), Some("text/plain"));
+        assert!(headers.is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{HashMap, Iter};
+
+// ObservedMap 在每次修改后回调，方法都有空的默认实现，只需要覆盖关心的事件
+pub trait MapHooks<K, V> {
+    fn on_insert(&mut self, _key: &K, _value: &V) {}
+
+    fn on_overwrite(&mut self, _key: &K, _old: &V, _new: &V) {}
+
+    fn on_remove(&mut self, _key: &K, _value: &V) {}
+
+    // 参数是扩容前后的桶数
+    fn on_resize(&mut self, _old_buckets: usize, _new_buckets: usize) {}
+}
+
+impl<K, V> MapHooks<K, V> for () {}
+
+// 所有修改都经过这里，方便维护二级索引和统计数据；不提供 get_mut，否则修改就绕过了回调
+pub struct ObservedMap<K, V, H> {
+    map: HashMap<K, V>,
+    hooks: H,
+}
+
+impl<K, V, H> ObservedMap<K, V, H> {
+    pub fn new(hooks: H) -> Self {
+        ObservedMap {
+            map: HashMap::new(),
+            hooks,
+        }
+    }
+
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    pub fn into_inner(self) -> (HashMap<K, V>, H) {
+        (self.map, self.hooks)
+    }
+}
+
+impl<K, V, H> ObservedMap<K, V, H>
+where
+    K: Hash + Eq,
+    H: MapHooks<K, V>,
+{
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.map.get_mut(&key) {
+            let old = std::mem::replace(slot, value);
+            self.hooks.on_overwrite(&key, &old, slot);
+            return Some(old);
+        }
+        // 先把扩容做掉，这样 on_resize 在 on_insert 之前触发，之后的插入也不会再扩容
+        let before = self.map.buckets.len();
+        self.map.reserve(1);
+        if self.map.buckets.len() != before {
+            self.hooks.on_resize(before, self.map.buckets.len());
+        }
+        let (k, v) = self.map.insert_unique_unchecked(key, value);
+        self.hooks.on_insert(k, v);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (k, v) = self.map.remove_entry(key)?;
+        self.hooks.on_remove(&k, &v);
+        Some(v)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Events {
+        log: Vec<String>,
+        resizes: usize,
+    }
+
+    impl MapHooks<&str, i32> for Events {
+        fn on_insert(&mut self, key: &&str, value: &i32) {
+            self.log.push(format!("insert {key}={value}"));
+        }
+
+        fn on_overwrite(&mut self, key: &&str, old: &i32, new: &i32) {
+            self.log.push(format!("overwrite {key} {old}->{new}"));
+        }
+
+        fn on_remove(&mut self, key: &&str, value: &i32) {
+            self.log.push(format!("remove {key}={value}"));
+        }
+
+        fn on_resize(&mut self, old_buckets: usize, new_buckets: usize) {
+            assert!(new_buckets > old_buckets);
+            self.resizes += 1;
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let mut map = ObservedMap::new(Events::default());
+        map.insert("a", 1);
+        map.insert("a", 2);
+        assert_eq!(map.remove("a"), Some(2));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(
+            map.hooks().log,
+            ["insert a=1", "overwrite a 1->2", "remove a=2"]
+        );
+        assert_eq!(map.hooks().resizes, 1);
+
+        let mut plain = ObservedMap::new(());
+        for i in 0..100 {
+            plain.insert(i, i);
+        }
+        assert_eq!(plain.len(), 100);
+    }
+}
@@ -0,0 +1,376 @@
+use std::{borrow::Borrow, hash::Hash, sync::Arc};
+
+use crate::make_hash;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+// Branch 用 bitmap 标记 32 个槽里哪些有孩子，children 只存有孩子的槽（按槽号排序）
+// Leaf 存哈希值完全相同的所有 entry
+enum Node<K, V> {
+    Branch {
+        bitmap: u32,
+        children: Vec<Arc<Node<K, V>>>,
+    },
+    Leaf {
+        hash: u64,
+        entries: Vec<Arc<(K, V)>>,
+    },
+}
+
+// 哈希数组映射字典树（HAMT）：insert / remove 只复制从根到叶子的一条路径，其余节点和旧 map 共享
+// entry 本身也用 Arc 共享，所以 K、V 不需要 Clone
+pub struct HashMap<K, V> {
+    root: Option<Arc<Node<K, V>>>,
+    len: usize,
+}
+
+impl<K, V> HashMap<K, V> {
+    pub fn new() -> Self {
+        HashMap { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: self.root.iter().map(|root| &**root).collect(),
+            leaf: [].iter(),
+        }
+    }
+}
+
+impl<K, V> Clone for HashMap<K, V> {
+    // O(1)，只复制根节点的引用
+    fn clone(&self) -> Self {
+        HashMap {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for HashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn slot(hash: u64, shift: u32) -> u32 {
+    ((hash >> shift) & MASK) as u32
+}
+
+// 两个哈希不同的节点，从 shift 这一层开始往下建分支，直到它们落到不同的槽
+fn merge<K, V>(
+    a: Arc<Node<K, V>>,
+    a_hash: u64,
+    b: Arc<Node<K, V>>,
+    b_hash: u64,
+    shift: u32,
+) -> Arc<Node<K, V>> {
+    let (a_slot, b_slot) = (slot(a_hash, shift), slot(b_hash, shift));
+    let node = if a_slot == b_slot {
+        Node::Branch {
+            bitmap: 1 << a_slot,
+            children: vec![merge(a, a_hash, b, b_hash, shift + BITS)],
+        }
+    } else if a_slot < b_slot {
+        Node::Branch {
+            bitmap: (1 << a_slot) | (1 << b_slot),
+            children: vec![a, b],
+        }
+    } else {
+        Node::Branch {
+            bitmap: (1 << a_slot) | (1 << b_slot),
+            children: vec![b, a],
+        }
+    };
+    Arc::new(node)
+}
+
+impl<K, V> HashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = make_hash(key);
+        let mut node = self.root.as_deref()?;
+        let mut shift = 0;
+        loop {
+            match node {
+                Node::Branch { bitmap, children } => {
+                    let bit = 1 << slot(hash, shift);
+                    if bitmap & bit == 0 {
+                        return None;
+                    }
+                    node = &children[(bitmap & (bit - 1)).count_ones() as usize];
+                    shift += BITS;
+                }
+                Node::Leaf {
+                    hash: lhash,
+                    entries,
+                } => {
+                    if *lhash != hash {
+                        return None;
+                    }
+                    return entries
+                        .iter()
+                        .find(|entry| entry.0.borrow() == key)
+                        .map(|entry| &entry.1);
+                }
+            }
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // 返回插入后的新 map，self 保持不变
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let hash = make_hash(&key);
+        let entry = Arc::new((key, value));
+        match &self.root {
+            None => HashMap {
+                root: Some(Arc::new(Node::Leaf {
+                    hash,
+                    entries: vec![entry],
+                })),
+                len: 1,
+            },
+            Some(root) => {
+                let (root, added) = Self::insert_node(root, 0, hash, entry);
+                HashMap {
+                    root: Some(root),
+                    len: self.len + added as usize,
+                }
+            }
+        }
+    }
+
+    fn insert_node(
+        node: &Arc<Node<K, V>>,
+        shift: u32,
+        hash: u64,
+        entry: Arc<(K, V)>,
+    ) -> (Arc<Node<K, V>>, bool) {
+        match &**node {
+            Node::Leaf {
+                hash: lhash,
+                entries,
+            } => {
+                if *lhash != hash {
+                    let leaf = Arc::new(Node::Leaf {
+                        hash,
+                        entries: vec![entry],
+                    });
+                    return (merge(node.clone(), *lhash, leaf, hash, shift), true);
+                }
+                let mut entries = entries.clone();
+                let added = match entries.iter().position(|e| e.0 == entry.0) {
+                    Some(pos) => {
+                        entries[pos] = entry;
+                        false
+                    }
+                    None => {
+                        entries.push(entry);
+                        true
+                    }
+                };
+                let leaf = Node::Leaf { hash, entries };
+                (Arc::new(leaf), added)
+            }
+            Node::Branch { bitmap, children } => {
+                let bit = 1 << slot(hash, shift);
+                let pos = (bitmap & (bit - 1)).count_ones() as usize;
+                let mut children = children.clone();
+                let added = if bitmap & bit == 0 {
+                    let leaf = Node::Leaf {
+                        hash,
+                        entries: vec![entry],
+                    };
+                    children.insert(pos, Arc::new(leaf));
+                    true
+                } else {
+                    let (child, added) =
+                        Self::insert_node(&children[pos], shift + BITS, hash, entry);
+                    children[pos] = child;
+                    added
+                };
+                let branch = Node::Branch {
+                    bitmap: bitmap | bit,
+                    children,
+                };
+                (Arc::new(branch), added)
+            }
+        }
+    }
+
+    // 返回删除后的新 map；key 不存在时返回的 map 和 self 共享全部节点
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(root) = &self.root else {
+            return self.clone();
+        };
+        match Self::remove_node(root, 0, make_hash(key), key) {
+            None => self.clone(),
+            Some(root) => HashMap {
+                root,
+                len: self.len - 1,
+            },
+        }
+    }
+
+    // None 表示没找到；Some(None) 表示节点删空了
+    fn remove_node<Q>(
+        node: &Arc<Node<K, V>>,
+        shift: u32,
+        hash: u64,
+        key: &Q,
+    ) -> Option<Option<Arc<Node<K, V>>>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &**node {
+            Node::Leaf {
+                hash: lhash,
+                entries,
+            } => {
+                if *lhash != hash {
+                    return None;
+                }
+                let pos = entries.iter().position(|e| e.0.borrow() == key)?;
+                if entries.len() == 1 {
+                    return Some(None);
+                }
+                let mut entries = entries.clone();
+                entries.remove(pos);
+                Some(Some(Arc::new(Node::Leaf { hash, entries })))
+            }
+            Node::Branch { bitmap, children } => {
+                let bit = 1 << slot(hash, shift);
+                if bitmap & bit == 0 {
+                    return None;
+                }
+                let pos = (bitmap & (bit - 1)).count_ones() as usize;
+                let child = Self::remove_node(&children[pos], shift + BITS, hash, key)?;
+                let mut children = children.clone();
+                let bitmap = match child {
+                    Some(child) => {
+                        children[pos] = child;
+                        *bitmap
+                    }
+                    None => {
+                        children.remove(pos);
+                        bitmap & !bit
+                    }
+                };
+                // 只剩一个叶子时把它提上来，保持树尽量浅
+                match children.as_slice() {
+                    [] => Some(None),
+                    [only] if matches!(**only, Node::Leaf { .. }) => Some(Some(only.clone())),
+                    _ => Some(Some(Arc::new(Node::Branch { bitmap, children }))),
+                }
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    leaf: std::slice::Iter<'a, Arc<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.leaf.next() {
+                break Some((&entry.0, &entry.1));
+            }
+            match self.stack.pop()? {
+                Node::Branch { children, .. } => {
+                    self.stack.extend(children.iter().map(|child| &**child));
+                }
+                Node::Leaf { entries, .. } => self.leaf = entries.iter(),
+            }
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a HashMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = HashMap::new();
+        for (k, v) in iter {
+            map = map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot() {
+        let v1: HashMap<_, _> = (0..1000).map(|i| (i, i)).collect();
+        let v2 = v1.insert(5, 50).insert(1000, 1000);
+        let v3 = v2.remove(&3).remove(&10_000);
+        assert_eq!(v1.len(), 1000);
+        assert_eq!(v2.len(), 1001);
+        assert_eq!(v3.len(), 1000);
+        assert_eq!(v1.get(&5), Some(&5));
+        assert_eq!(v2.get(&5), Some(&50));
+        assert_eq!(v1.get(&1000), None);
+        assert_eq!(v2.get(&3), Some(&3));
+        assert_eq!(v3.get(&3), None);
+        assert_eq!(v3.iter().count(), 1000);
+        assert_eq!(v1.iter().map(|(_, &v)| v).sum::<i32>(), 999 * 1000 / 2);
+    }
+
+    #[test]
+    fn remove_all() {
+        let mut map: HashMap<_, _> = (0..100).map(|i| (i.to_string(), i)).collect();
+        for i in 0..100 {
+            map = map.remove(i.to_string().as_str());
+            assert_eq!(map.len(), 99 - i);
+        }
+        assert!(map.is_empty());
+        assert!(map.root.is_none());
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use crate::{hash::StableHasher, HashMap};
+
+// 平均每个位移桶里的 key 个数，越大生成的 disps 表越小，但生成越慢
+const LAMBDA: usize = 5;
+
+struct Hashes {
+    g: u32,
+    f1: u32,
+    f2: u32,
+}
+
+// build script 在宿主机上运行，生成的表可能在另一个平台上、用另一个版本的 Rust 编译的程序里查找，
+// 所以用 StableHasher：结果和平台的字长、字节序以及 Rust 版本都无关
+fn hash<Q>(seed: u64, key: &Q) -> Hashes
+where
+    Q: Hash + ?Sized,
+{
+    let mut hasher = StableHasher::default();
+    hasher.write_u64(seed);
+    key.hash(&mut hasher);
+    let h = hasher.finish();
+    Hashes {
+        g: (h >> 32) as u32,
+        f1: h as u32,
+        f2: (h.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as u32,
+    }
+}
+
+fn displace(f1: u32, f2: u32, d1: u32, d2: u32) -> u32 {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2)
+}
+
+// 完美哈希（CHD）：key 先按 g 分到位移桶，再用该桶的 (d1, d2) 算出在 entries 中唯一的位置
+// 所有数据都是 'static 切片，由 StaticMapBuilder 在 build script 中生成代码
+pub struct StaticMap<K: 'static, V: 'static> {
+    seed: u64,
+    disps: &'static [(u32, u32)],
+    entries: &'static [(K, V)],
+}
+
+impl<K, V> StaticMap<K, V> {
+    // 只给生成的代码用，参数必须来自 StaticMapBuilder
+    pub const fn new(seed: u64, disps: &'static [(u32, u32)], entries: &'static [(K, V)]) -> Self {
+        StaticMap {
+            seed,
+            disps,
+            entries,
+        }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static K, &'static V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K, V> StaticMap<K, V>
+where
+    K: Hash + Eq,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&'static V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let hashes = hash(self.seed, key);
+        let (d1, d2) = self.disps[hashes.g as usize % self.disps.len()];
+        let idx = displace(hashes.f1, hashes.f2, d1, d2) as usize % self.entries.len();
+        let (ekey, evalue) = &self.entries[idx];
+        (ekey.borrow() == key).then_some(evalue)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+// 生成结果：disps 是每个位移桶的 (d1, d2)，order[i] 是放在 entries[i] 的 key 的原始下标
+pub struct HashState {
+    pub seed: u64,
+    pub disps: Vec<(u32, u32)>,
+    pub order: Vec<usize>,
+}
+
+pub fn generate_hash<K>(keys: &[K]) -> HashState
+where
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::new();
+    for key in keys {
+        assert!(
+            seen.insert(key, ()).is_none(),
+            "duplicate key in static map"
+        );
+    }
+    (0..)
+        .find_map(|seed| try_generate_hash(keys, seed))
+        .unwrap()
+}
+
+fn try_generate_hash<K>(keys: &[K], seed: u64) -> Option<HashState>
+where
+    K: Hash,
+{
+    let hashes: Vec<_> = keys.iter().map(|key| hash(seed, key)).collect();
+    let table_len = hashes.len();
+    if table_len == 0 {
+        return Some(HashState {
+            seed,
+            disps: Vec::new(),
+            order: Vec::new(),
+        });
+    }
+    let nbuckets = table_len.div_ceil(LAMBDA).max(1);
+
+    let mut buckets: Vec<(usize, Vec<usize>)> = (0..nbuckets).map(|i| (i, Vec::new())).collect();
+    for (i, h) in hashes.iter().enumerate() {
+        buckets[h.g as usize % nbuckets].1.push(i);
+    }
+    // 先放 key 多的桶，越往后可选的位置越少
+    buckets.sort_by_key(|(_, keys)| std::cmp::Reverse(keys.len()));
+
+    let mut map: Vec<Option<usize>> = vec![None; table_len];
+    let mut disps = vec![(0, 0); nbuckets];
+    // try_map[i] == generation 表示位置 i 在本轮尝试中已被占用，省去每轮清空
+    let mut try_map = vec![0u64; table_len];
+    let mut generation = 0u64;
+    let mut values_to_add = Vec::new();
+
+    'buckets: for (bucket, bucket_keys) in &buckets {
+        for d1 in 0..table_len as u32 {
+            'disps: for d2 in 0..table_len as u32 {
+                values_to_add.clear();
+                generation += 1;
+                for &key in bucket_keys {
+                    let h = &hashes[key];
+                    let idx = displace(h.f1, h.f2, d1, d2) as usize % table_len;
+                    if map[idx].is_some() || try_map[idx] == generation {
+                        continue 'disps;
+                    }
+                    try_map[idx] = generation;
+                    values_to_add.push((idx, key));
+                }
+                disps[*bucket] = (d1, d2);
+                for &(idx, key) in &values_to_add {
+                    map[idx] = Some(key);
+                }
+                continue 'buckets;
+            }
+        }
+        return None;
+    }
+
+    Some(HashState {
+        seed,
+        disps,
+        order: map.into_iter().map(|i| i.unwrap_or(0)).collect(),
+    })
+}
+
+// 在 build script 里使用：
+//     let mut builder = StaticMapBuilder::new();
+//     builder.entry("foo", "1").entry("bar", "2");
+//     writeln!(file, "static MAP: StaticMap<&str, u32> = {};", builder.build())
+// value 是一段 Rust 表达式源码，key 用 Debug 格式输出，所以要求 Debug 输出就是合法的字面量
+pub struct StaticMapBuilder<K> {
+    keys: Vec<K>,
+    values: Vec<String>,
+}
+
+impl<K> StaticMapBuilder<K> {
+    pub fn new() -> Self {
+        StaticMapBuilder {
+            keys: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn entry(&mut self, key: K, value: &str) -> &mut Self {
+        self.keys.push(key);
+        self.values.push(value.to_string());
+        self
+    }
+}
+
+impl<K> Default for StaticMapBuilder<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> StaticMapBuilder<K>
+where
+    K: Hash + Eq + fmt::Debug,
+{
+    pub fn build(&self) -> DisplayStaticMap<'_, K> {
+        DisplayStaticMap {
+            state: generate_hash(&self.keys),
+            keys: &self.keys,
+            values: &self.values,
+        }
+    }
+}
+
+pub struct DisplayStaticMap<'a, K> {
+    state: HashState,
+    keys: &'a [K],
+    values: &'a [String],
+}
+
+impl<K> fmt::Display for DisplayStaticMap<'_, K>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "::rust_hashmap::phf::StaticMap::new(")?;
+        writeln!(f, "    {},", self.state.seed)?;
+        write!(f, "    &[")?;
+        for (d1, d2) in &self.state.disps {
+            write!(f, "({d1}, {d2}), ")?;
+        }
+        writeln!(f, "],")?;
+        writeln!(f, "    &[")?;
+        for &i in &self.state.order {
+            writeln!(f, "        ({:?}, {}),", self.keys[i], self.values[i])?;
+        }
+        writeln!(f, "    ],")?;
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leak<K: Hash + Eq + Clone, V: Clone>(keys: &[K], values: &[V]) -> StaticMap<K, V> {
+        let state = generate_hash(keys);
+        let entries: Vec<_> = state
+            .order
+            .iter()
+            .map(|&i| (keys[i].clone(), values[i].clone()))
+            .collect();
+        StaticMap::new(state.seed, Vec::leak(state.disps), Vec::leak(entries))
+    }
+
+    #[test]
+    fn lookup() {
+        let keys: Vec<String> = (0..200).map(|i| format!("key{i}")).collect();
+        let values: Vec<usize> = (0..200).collect();
+        let map = leak(&keys, &values);
+        assert_eq!(map.len(), 200);
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(map.get(key.as_str()), Some(&i));
+        }
+        assert_eq!(map.get("key200"), None);
+
+        let empty = leak::<u32, u32>(&[], &[]);
+        assert_eq!(empty.get(&1), None);
+    }
+
+    #[test]
+    fn stable_hashes() {
+        // 固定的期望值：生成表的机器和查表的机器必须算出同样的结果
+        let h = hash(7, "foo");
+        assert_eq!((h.g, h.f1, h.f2), (0x24d3_8ae9, 0x11a1_27d4, 0x9396_5134));
+    }
+
+    #[test]
+    fn codegen() {
+        let mut builder = StaticMapBuilder::new();
+        builder.entry("foo", "1").entry("bar", "2 + 3");
+        let code = builder.build().to_string();
+        assert!(code.starts_with("::rust_hashmap::phf::StaticMap::new("));
+        assert!(code.contains("(\"foo\", 1),"));
+        assert!(code.contains("(\"bar\", 2 + 3),"));
+    }
+}
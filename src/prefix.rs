@@ -0,0 +1,225 @@
+use crate::{HashMap, Iter};
+
+struct Node {
+    byte: u8,
+    // 按字节排序
+    children: Vec<(u8, usize)>,
+    // 子树里（包括自己）还在表中的 key 数；删除时只减计数，空掉的分支留给之后的插入复用
+    count: usize,
+    terminal: bool,
+}
+
+impl Node {
+    fn new(byte: u8) -> Self {
+        Node {
+            byte,
+            children: Vec::new(),
+            count: 0,
+            terminal: false,
+        }
+    }
+}
+
+// 字符串 key 的表：精确查找走哈希表，旁边按字节维护一棵 trie 做前缀索引，
+// iter_prefix 按字典序列出所有以某个前缀开头的 entry，用来做自动补全一类的查询
+pub struct PrefixMap<V> {
+    map: HashMap<String, V>,
+    nodes: Vec<Node>,
+}
+
+impl<V> PrefixMap<V> {
+    pub fn new() -> Self {
+        PrefixMap {
+            map: HashMap::new(),
+            nodes: vec![Node::new(0)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // 哈希表的顺序；要字典序用 iter_prefix("")
+    pub fn iter(&self) -> Iter<'_, String, V> {
+        self.map.iter()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.map.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: String, value: V) -> Option<V> {
+        if let Some(slot) = self.map.get_mut(key.as_str()) {
+            return Some(std::mem::replace(slot, value));
+        }
+        let mut node = 0;
+        self.nodes[node].count += 1;
+        for &byte in key.as_bytes() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&byte, |&(b, _)| b)
+            {
+                Ok(i) => self.nodes[node].children[i].1,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::new(byte));
+                    self.nodes[node].children.insert(i, (byte, child));
+                    child
+                }
+            };
+            self.nodes[node].count += 1;
+        }
+        self.nodes[node].terminal = true;
+        self.map.insert_unique_unchecked(key, value);
+        None
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let value = self.map.remove(key)?;
+        let mut node = 0;
+        self.nodes[node].count -= 1;
+        for &byte in key.as_bytes() {
+            node = self.child(node, byte).unwrap();
+            self.nodes[node].count -= 1;
+        }
+        self.nodes[node].terminal = false;
+        Some(value)
+    }
+
+    fn child(&self, node: usize, byte: u8) -> Option<usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by_key(&byte, |&(b, _)| b)
+            .ok()
+            .map(|i| children[i].1)
+    }
+
+    fn find(&self, prefix: &str) -> Option<usize> {
+        prefix
+            .bytes()
+            .try_fold(0, |node, byte| self.child(node, byte))
+            .filter(|&node| self.nodes[node].count > 0)
+    }
+
+    // 以 prefix 开头的 key 个数，不用遍历
+    pub fn count_prefix(&self, prefix: &str) -> usize {
+        self.find(prefix).map_or(0, |node| self.nodes[node].count)
+    }
+
+    // 按字典序（字节序）列出以 prefix 开头的 entry
+    pub fn iter_prefix(&self, prefix: &str) -> PrefixIter<'_, V> {
+        PrefixIter {
+            map: self,
+            stack: self
+                .find(prefix)
+                .map(|node| (node, prefix.len()))
+                .into_iter()
+                .collect(),
+            buf: prefix.as_bytes().to_vec(),
+            root_depth: prefix.len(),
+        }
+    }
+}
+
+impl<V> Default for PrefixMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(String, V)> for PrefixMap<V> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (String, V)>,
+    {
+        let mut map = PrefixMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+// 在 trie 上做先序遍历，buf 是当前节点对应的 key；走到 key 的终点再回哈希表取 entry
+pub struct PrefixIter<'a, V> {
+    map: &'a PrefixMap<V>,
+    // (节点, 节点的深度)
+    stack: Vec<(usize, usize)>,
+    buf: Vec<u8>,
+    root_depth: usize,
+}
+
+impl<'a, V> Iterator for PrefixIter<'a, V> {
+    type Item = (&'a str, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((idx, depth)) = self.stack.pop() {
+            let node = &self.map.nodes[idx];
+            if depth > self.root_depth {
+                self.buf.truncate(depth - 1);
+                self.buf.push(node.byte);
+            }
+            self.stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .filter(|&&(_, child)| self.map.nodes[child].count > 0)
+                    .map(|&(_, child)| (child, depth + 1)),
+            );
+            if node.terminal {
+                // 终点上的 buf 和某个 key 的字节完全相同，一定是合法的 UTF-8
+                let key = std::str::from_utf8(&self.buf).unwrap();
+                let bucket_idx = self.map.map.bucket_idx(key).unwrap();
+                return self.map.map.buckets[bucket_idx]
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(k, v)| (k.as_str(), v));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autocomplete() {
+        let mut map: PrefixMap<_> = ["car", "card", "care", "cat", "dog", "carton"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, k)| (k.to_string(), i))
+            .collect();
+        assert_eq!(map.get("cat"), Some(&3));
+        assert_eq!(map.insert("car".to_string(), 10), Some(0));
+
+        let keys: Vec<_> = map.iter_prefix("car").map(|(k, _)| k).collect();
+        assert_eq!(keys, ["car", "card", "care", "carton"]);
+        assert_eq!(map.count_prefix("ca"), 5);
+        assert_eq!(map.iter_prefix("").count(), 6);
+        assert_eq!(map.iter_prefix("cow").next(), None);
+
+        assert_eq!(map.remove("card"), Some(1));
+        assert_eq!(map.remove("card"), None);
+        assert_eq!(map.remove("car"), Some(10));
+        let entries: Vec<_> = map.iter_prefix("car").collect();
+        assert_eq!(entries, [("care", &2), ("carton", &5)]);
+        assert_eq!(map.count_prefix("card"), 0);
+
+        map.insert("card".to_string(), 11);
+        assert_eq!(map.iter_prefix("card").collect::<Vec<_>>(), [("card", &11)]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::hash::{Hash, Hasher};
+
+use pyo3::{
+    exceptions::{PyKeyError, PyValueError},
+    prelude::*,
+    types::{PyIterator, PyList},
+};
+
+use crate::{linked::LinkedHashMap, Counter, HashMap};
+
+// Python 对象做 key：构造时算好 __hash__，比较时回到解释器里调用 __eq__
+struct PyKey {
+    hash: isize,
+    obj: Py<PyAny>,
+}
+
+impl PyKey {
+    fn new(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(PyKey {
+            hash: obj.hash()?,
+            obj: obj.clone().unbind(),
+        })
+    }
+}
+
+impl Hash for PyKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+// __eq__ 抛出的异常没法从这里传出去，当作不相等处理
+impl PartialEq for PyKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && Python::attach(|py| self.obj.bind(py).eq(other.obj.bind(py)).unwrap_or(false))
+    }
+}
+
+impl Eq for PyKey {}
+
+fn key_error(key: &Bound<'_, PyAny>) -> PyErr {
+    PyKeyError::new_err(key.clone().unbind())
+}
+
+// 先把 key 复制到 list 里再迭代，迭代期间修改表不会出错，但也看不到修改
+fn iter_keys<'a, 'py>(
+    py: Python<'py>,
+    keys: impl Iterator<Item = &'a PyKey>,
+) -> PyResult<Bound<'py, PyIterator>> {
+    PyList::new(py, keys.map(|key| key.obj.bind(py)))?.try_iter()
+}
+
+#[pyclass(name = "HashMap", mapping)]
+pub struct PyHashMap {
+    map: HashMap<PyKey, Py<PyAny>>,
+}
+
+#[pymethods]
+impl PyHashMap {
+    #[new]
+    fn new() -> Self {
+        PyHashMap {
+            map: HashMap::new(),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.map.len()
+    }
+
+    fn __contains__(&self, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        Ok(self.map.contains_key(&PyKey::new(key)?))
+    }
+
+    fn __getitem__(&self, key: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        match self.map.get(&PyKey::new(key)?) {
+            Some(value) => Ok(value.clone_ref(key.py())),
+            None => Err(key_error(key)),
+        }
+    }
+
+    fn __setitem__(&mut self, key: &Bound<'_, PyAny>, value: Py<PyAny>) -> PyResult<()> {
+        self.map.insert(PyKey::new(key)?, value);
+        Ok(())
+    }
+
+    fn __delitem__(&mut self, key: &Bound<'_, PyAny>) -> PyResult<()> {
+        match self.map.remove(&PyKey::new(key)?) {
+            Some(_) => Ok(()),
+            None => Err(key_error(key)),
+        }
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        iter_keys(py, self.map.keys())
+    }
+
+    #[pyo3(signature = (key, default = None))]
+    fn get(
+        &self,
+        key: &Bound<'_, PyAny>,
+        default: Option<Py<PyAny>>,
+    ) -> PyResult<Option<Py<PyAny>>> {
+        Ok(match self.map.get(&PyKey::new(key)?) {
+            Some(value) => Some(value.clone_ref(key.py())),
+            None => default,
+        })
+    }
+
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        PyList::new(
+            py,
+            self.map.iter().map(|(k, v)| (k.obj.bind(py), v.bind(py))),
+        )
+    }
+}
+
+// 和 collections.Counter 一样，不存在的元素计数为 0
+#[pyclass(name = "Counter", mapping)]
+pub struct PyCounter {
+    counter: Counter<PyKey>,
+}
+
+#[pymethods]
+impl PyCounter {
+    #[new]
+    #[pyo3(signature = (iterable = None))]
+    fn new(iterable: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let mut counter = Counter::new();
+        if let Some(iterable) = iterable {
+            for item in iterable.try_iter()? {
+                counter.add(PyKey::new(&item?)?);
+            }
+        }
+        Ok(PyCounter { counter })
+    }
+
+    fn __len__(&self) -> usize {
+        self.counter.len()
+    }
+
+    fn __contains__(&self, item: &Bound<'_, PyAny>) -> PyResult<bool> {
+        Ok(self.counter.count(&PyKey::new(item)?) > 0)
+    }
+
+    fn __getitem__(&self, item: &Bound<'_, PyAny>) -> PyResult<usize> {
+        Ok(self.counter.count(&PyKey::new(item)?))
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        iter_keys(py, self.counter.iter().map(|(item, _)| item))
+    }
+
+    // 返回加完之后的计数
+    #[pyo3(signature = (item, n = 1))]
+    fn add(&mut self, item: &Bound<'_, PyAny>, n: usize) -> PyResult<usize> {
+        Ok(self.counter.add_n(PyKey::new(item)?, n))
+    }
+
+    fn total(&self) -> usize {
+        self.counter.total()
+    }
+
+    #[pyo3(signature = (n = None))]
+    fn most_common<'py>(&self, py: Python<'py>, n: Option<usize>) -> PyResult<Bound<'py, PyList>> {
+        let items = self.counter.most_common();
+        let n = n.unwrap_or(items.len());
+        PyList::new(
+            py,
+            items
+                .into_iter()
+                .take(n)
+                .map(|(item, count)| (item.obj.bind(py), count)),
+        )
+    }
+}
+
+// 按最近访问排序的 LRU 缓存，超出容量时淘汰最久没有访问的 entry；迭代从最旧到最新
+#[pyclass(name = "LruCache", mapping)]
+pub struct PyLruCache {
+    map: LinkedHashMap<PyKey, Py<PyAny>>,
+    capacity: usize,
+}
+
+#[pymethods]
+impl PyLruCache {
+    #[new]
+    fn new(capacity: usize) -> PyResult<Self> {
+        if capacity == 0 {
+            return Err(PyValueError::new_err("capacity must be positive"));
+        }
+        Ok(PyLruCache {
+            map: LinkedHashMap::with_access_order(),
+            capacity,
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.map.len()
+    }
+
+    // 不算一次访问
+    fn __contains__(&self, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        Ok(self.map.contains_key(&PyKey::new(key)?))
+    }
+
+    fn __getitem__(&mut self, key: &Bound<'_, PyAny>) -> PyResult<Py<PyAny>> {
+        match self.map.get(&PyKey::new(key)?) {
+            Some(value) => Ok(value.clone_ref(key.py())),
+            None => Err(key_error(key)),
+        }
+    }
+
+    fn __setitem__(&mut self, key: &Bound<'_, PyAny>, value: Py<PyAny>) -> PyResult<()> {
+        self.map.insert(PyKey::new(key)?, value);
+        while self.map.len() > self.capacity {
+            self.map.pop_front();
+        }
+        Ok(())
+    }
+
+    fn __delitem__(&mut self, key: &Bound<'_, PyAny>) -> PyResult<()> {
+        match self.map.remove(&PyKey::new(key)?) {
+            Some(_) => Ok(()),
+            None => Err(key_error(key)),
+        }
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        iter_keys(py, self.map.iter().map(|(key, _)| key))
+    }
+}
+
+#[pymodule]
+fn rust_hashmap(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyHashMap>()?;
+    m.add_class::<PyCounter>()?;
+    m.add_class::<PyLruCache>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classes() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "rust_hashmap")?;
+            rust_hashmap(&module)?;
+            py.run(
+                cr#"
+m = HashMap()
+m["a"] = 1
+m[2] = [2]
+m[(1, 2)] = "t"
+assert len(m) == 3 and m["a"] == 1 and m[(1, 2)] == "t" and "a" in m
+del m["a"]
+assert "a" not in m and m.get("a", 5) == 5
+assert sorted(map(str, m)) == ["(1, 2)", "2"]
+try:
+    m["a"]
+    assert False
+except KeyError:
+    pass
+
+c = Counter("abracadabra")
+assert c["a"] == 5 and c["z"] == 0 and c.total() == 11
+assert c.most_common(1) == [("a", 5)]
+assert c.add("z", 2) == 2
+
+lru = LruCache(2)
+lru[1] = "x"
+lru[2] = "y"
+lru[1]
+lru[3] = "z"
+assert 2 not in lru and list(lru) == [1, 3]
+"#,
+                Some(&module.dict()),
+                None,
+            )
+        })
+        .unwrap();
+    }
+}
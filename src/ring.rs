@@ -0,0 +1,136 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::StableState;
+
+// 默认每个节点在环上放多少个虚拟副本；副本越多负载越均匀，环也越大
+const DEFAULT_REPLICAS: usize = 160;
+
+// 一致性哈希环：每个节点按 (节点, 副本编号) 哈希出 replicas 个点，key 落在顺时针方向的第一个点所属的节点上。
+// 增删一个节点只会移动大约 1/n 的 key。默认用 StableState，它的结果不随进程、Rust 版本和平台变化，
+// 只要节点和 key 的 Hash 实现不变，不同机器、不同构建的客户端上同样的节点得到同样的环，路由结果一致
+pub struct HashRing<N, S = StableState> {
+    // 按哈希值排序的虚拟节点
+    points: Vec<(u64, N)>,
+    replicas: usize,
+    hash_builder: S,
+}
+
+impl<N> HashRing<N> {
+    pub fn new() -> Self {
+        Self::with_replicas(DEFAULT_REPLICAS)
+    }
+
+    pub fn with_replicas(replicas: usize) -> Self {
+        Self::with_replicas_and_hasher(replicas, StableState::default())
+    }
+}
+
+impl<N, S> HashRing<N, S> {
+    pub fn with_replicas_and_hasher(replicas: usize, hash_builder: S) -> Self {
+        assert!(replicas > 0, "replicas must be positive");
+        HashRing {
+            points: Vec::new(),
+            replicas,
+            hash_builder,
+        }
+    }
+
+    // 节点个数
+    pub fn len(&self) -> usize {
+        self.points.len() / self.replicas
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+impl<N> Default for HashRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, S> HashRing<N, S>
+where
+    N: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    pub fn contains(&self, node: &N) -> bool {
+        self.points.iter().any(|(_, n)| n == node)
+    }
+
+    // 节点已经在环上时返回 false
+    pub fn add(&mut self, node: N) -> bool {
+        if self.contains(&node) {
+            return false;
+        }
+        // 副本编号按固定宽度哈希，32 位和 64 位目标上一样
+        for replica in 0..self.replicas as u64 {
+            let hash = self.hash_builder.hash_one((&node, replica));
+            let idx = self.points.partition_point(|(h, _)| *h < hash);
+            self.points.insert(idx, (hash, node.clone()));
+        }
+        true
+    }
+
+    pub fn remove(&mut self, node: &N) -> bool {
+        let before = self.points.len();
+        self.points.retain(|(_, n)| n != node);
+        self.points.len() != before
+    }
+
+    // key 所属的节点，环为空时返回 None
+    pub fn get<Q>(&self, key: &Q) -> Option<&N>
+    where
+        Q: Hash + ?Sized,
+    {
+        if self.points.is_empty() {
+            return None;
+        }
+        let hash = self.hash_builder.hash_one(key);
+        let idx = self.points.partition_point(|(h, _)| *h < hash);
+        Some(&self.points[idx % self.points.len()].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashMap;
+
+    #[test]
+    fn rebalance() {
+        let mut ring = HashRing::new();
+        assert_eq!(ring.get("k"), None);
+        for node in ["a", "b", "c", "d"] {
+            assert!(ring.add(node));
+        }
+        assert!(!ring.add("a"));
+        assert_eq!(ring.len(), 4);
+
+        let owners: HashMap<u32, &str> = (0..10_000).map(|k| (k, *ring.get(&k).unwrap())).collect();
+        let mut load: HashMap<&str, usize> = HashMap::new();
+        for node in owners.values() {
+            *load.entry(*node).or_default() += 1;
+        }
+        assert!(load.values().all(|&n| n > 1500 && n < 3500), "{load:?}");
+
+        // 删掉一个节点只移动原来属于它的 key
+        assert!(ring.remove(&"b"));
+        assert!(!ring.remove(&"b"));
+        assert!(!ring.contains(&"b"));
+        for (k, node) in &owners {
+            let now = *ring.get(k).unwrap();
+            assert!(now == *node || *node == "b");
+        }
+
+        // 路由结果是固定的，不依赖进程和平台
+        let mut ring = HashRing::new();
+        for node in ["a", "b", "c"] {
+            ring.add(node);
+        }
+        let routes: Vec<_> = (0..8u32).map(|k| *ring.get(&k).unwrap()).collect();
+        assert_eq!(routes, ["b", "b", "b", "c", "b", "a", "a", "b"]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::HashMap;
+
+// 一层一层叠起来的作用域：查找从最内层往外找，插入总是写到最内层；
+// 适合解释器和符号表，内层的同名 key 会遮住外层，pop_scope 后外层的又重新可见
+pub struct ScopedMap<K, V> {
+    // 至少有一层（最外层），不能被弹出
+    scopes: Vec<HashMap<K, V>>,
+}
+
+impl<K, V> ScopedMap<K, V> {
+    pub fn new() -> Self {
+        ScopedMap {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    // 包括最外层在内的层数
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    // 新的一层是空表，还没有分配内存
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    // 弹出最内层并返回它的内容；只剩最外层时返回 None
+    pub fn pop_scope(&mut self) -> Option<HashMap<K, V>> {
+        if self.scopes.len() == 1 {
+            return None;
+        }
+        self.scopes.pop()
+    }
+
+    fn innermost(&mut self) -> &mut HashMap<K, V> {
+        self.scopes.last_mut().unwrap()
+    }
+}
+
+impl<K, V> Default for ScopedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ScopedMap<K, V>
+where
+    K: Hash + Eq,
+{
+    // 只会覆盖最内层的同名 key，外层的不受影响
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.innermost().insert(key, value)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.scopes.iter().rev().find_map(|scope| scope.get(key))
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(key))
+    }
+
+    // 只查最内层，用来判断是不是在同一个作用域里重复定义
+    pub fn get_local<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.scopes.last().unwrap().get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // 只从最内层删除
+    pub fn remove_local<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.innermost().remove(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shadowing() {
+        let mut map = ScopedMap::new();
+        map.insert("x", 1);
+        map.insert("y", 2);
+        map.push_scope();
+        map.insert("x", 10);
+        assert_eq!(map.get("x"), Some(&10));
+        assert_eq!(map.get("y"), Some(&2));
+        assert_eq!(map.get_local("y"), None);
+        *map.get_mut("y").unwrap() += 1;
+
+        let inner = map.pop_scope().unwrap();
+        assert_eq!(inner.len(), 1);
+        assert_eq!(map.get("x"), Some(&1));
+        assert_eq!(map.get("y"), Some(&3));
+        assert!(map.pop_scope().is_none());
+        assert_eq!(map.depth(), 1);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    ops::{BitAnd, BitOr, BitXor, Sub},
+};
+
+use crate::{DefaultHashBuilder, HashMap, IntoIter, Keys};
+
+// 建在 HashMap<T, ()> 上的集合，() 不占空间，和只存 key 的表一样大；
+// 接口和 std::collections::HashSet 对齐，可以直接替换
+#[derive(Clone)]
+pub struct HashSet<T, S = DefaultHashBuilder> {
+    map: HashMap<T, (), S>,
+}
+
+impl<T> HashSet<T> {
+    pub fn new() -> Self {
+        HashSet {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        HashSet {
+            map: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<T, S> HashSet<T, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        HashSet {
+            map: HashMap::with_hasher(hash_builder),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn iter(&self) -> Keys<'_, T, ()> {
+        self.map.keys()
+    }
+}
+
+impl<T, S> HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    // 已经存在时返回 false，集合里原来的那个值保留不动
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.map.contains_key(&value) {
+            return false;
+        }
+        self.map.insert_unique_unchecked(value, ());
+        true
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    // 在 self 里的，加上 other 里有、self 里没有的
+    pub fn union<'a>(&'a self, other: &'a HashSet<T, S>) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().chain(other.difference(self))
+    }
+
+    // 遍历较小的一边，去较大的一边里查
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a HashSet<T, S>,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        small.iter().filter(move |v| large.contains(*v))
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a HashSet<T, S>) -> impl Iterator<Item = &'a T> + 'a {
+        self.iter().filter(move |v| !other.contains(*v))
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a HashSet<T, S>,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        self.difference(other).chain(other.difference(self))
+    }
+
+    pub fn is_disjoint(&self, other: &HashSet<T, S>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    pub fn is_subset(&self, other: &HashSet<T, S>) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+
+    pub fn is_superset(&self, other: &HashSet<T, S>) -> bool {
+        other.is_subset(self)
+    }
+}
+
+impl<T, S> Default for HashSet<T, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T, S> fmt::Debug for HashSet<T, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> PartialEq for HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> Eq for HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+}
+
+impl<T, S> FromIterator<T> for HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = HashSet::with_hasher(S::default());
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T, S> Extend<T> for HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a HashSet<T, S> {
+    type Item = &'a T;
+
+    type IntoIter = Keys<'a, T, ()>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> IntoIterator for HashSet<T, S> {
+    type Item = T;
+
+    type IntoIter = std::iter::Map<IntoIter<T, ()>, fn((T, ())) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter().map(|(v, ())| v)
+    }
+}
+
+// &a | &b 这类运算符，和 std 一样克隆元素得到一个新集合，哈希器用 S::default()
+macro_rules! set_op {
+    ($trait:ident, $method:ident, $op:ident) => {
+        impl<T, S> $trait<&HashSet<T, S>> for &HashSet<T, S>
+        where
+            T: Hash + Eq + Clone,
+            S: BuildHasher + Default,
+        {
+            type Output = HashSet<T, S>;
+
+            fn $method(self, rhs: &HashSet<T, S>) -> HashSet<T, S> {
+                self.$op(rhs).cloned().collect()
+            }
+        }
+    };
+}
+
+set_op!(BitOr, bitor, union);
+set_op!(BitAnd, bitand, intersection);
+set_op!(Sub, sub, difference);
+set_op!(BitXor, bitxor, symmetric_difference);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_algebra() {
+        let a: HashSet<i32> = (0..6).collect();
+        let b: HashSet<i32> = (4..10).collect();
+        let sorted = |it: &mut dyn Iterator<Item = &i32>| {
+            let mut v: Vec<i32> = it.copied().collect();
+            v.sort();
+            v
+        };
+        assert_eq!(sorted(&mut a.union(&b)), (0..10).collect::<Vec<_>>());
+        assert_eq!(sorted(&mut a.intersection(&b)), [4, 5]);
+        assert_eq!(sorted(&mut a.difference(&b)), [0, 1, 2, 3]);
+        assert_eq!(
+            sorted(&mut a.symmetric_difference(&b)),
+            [0, 1, 2, 3, 6, 7, 8, 9]
+        );
+
+        let small: HashSet<i32> = [1, 2].into_iter().collect();
+        assert!(small.is_subset(&a) && a.is_superset(&small));
+        assert!(!a.is_subset(&b));
+        assert!(small.is_disjoint(&b) && !a.is_disjoint(&b));
+        assert_eq!(a.union(&b).collect::<HashSet<_>>().len(), 10);
+        assert_eq!(a, (0..6).rev().collect());
+    }
+
+    #[test]
+    fn operators() {
+        let a: HashSet<i32> = [1, 2, 3].into_iter().collect();
+        let b: HashSet<i32> = [2, 3, 4].into_iter().collect();
+        assert_eq!(&a | &b, [1, 2, 3, 4].into_iter().collect());
+        assert_eq!(&a & &b, [2, 3].into_iter().collect());
+        assert_eq!(&a - &b, [1].into_iter().collect());
+        assert_eq!(&a ^ &b, [1, 4].into_iter().collect());
+        assert_eq!(a.len(), 3);
+    }
+}
@@ -0,0 +1,177 @@
+use std::{future::Future, hash::Hash, sync::PoisonError};
+
+// loom 模型测试时换成 loom 的同步原语，见下面的 loom_tests
+#[cfg(loom)]
+use loom::sync::{Arc, Mutex, MutexGuard};
+#[cfg(not(loom))]
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::OnceCell;
+
+use crate::HashMap;
+
+// 合并同一个 key 上并发的加载：同时未命中的 N 个调用只有一个真正执行 loader，其余的等它的结果，
+// 用来防止缓存击穿时所有请求一起打到后端。只合并正在进行的加载，结束之后的调用会重新加载，
+// 结果要不要缓存由调用方决定。
+// 取消安全：执行 loader 的调用被 drop（比如外层超时）时，等待中的某一个调用接着用它自己的 loader 加载，
+// 不会有人永远等下去；loader 出错也一样，错误只返回给出错的那个调用
+pub struct SingleFlight<K, V> {
+    inflight: Mutex<HashMap<K, Arc<OnceCell<V>>>>,
+}
+
+impl<K, V> SingleFlight<K, V> {
+    pub fn new() -> Self {
+        SingleFlight {
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // 锁内只有 HashMap 的操作，poison 之后继续使用是安全的
+    fn inflight(&self) -> MutexGuard<'_, HashMap<K, Arc<OnceCell<V>>>> {
+        self.inflight.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K, V> Default for SingleFlight<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> SingleFlight<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    // 正在进行中的加载个数
+    pub fn len(&self) -> usize {
+        self.inflight().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub async fn load<L, Fut, E>(&self, key: K, loader: L) -> Result<V, E>
+    where
+        L: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let cell = Arc::clone(self.inflight().entry(key.clone()).or_default());
+        let _guard = Flight {
+            group: self,
+            key,
+            cell: &cell,
+        };
+        cell.get_or_try_init(loader).await.cloned()
+    }
+}
+
+// 调用结束（包括被取消）时，加载已经完成或者已经没有别人在等，就把记录摘掉
+struct Flight<'a, K, V>
+where
+    K: Hash + Eq,
+{
+    group: &'a SingleFlight<K, V>,
+    key: K,
+    cell: &'a Arc<OnceCell<V>>,
+}
+
+impl<K, V> Drop for Flight<'_, K, V>
+where
+    K: Hash + Eq,
+{
+    fn drop(&mut self) {
+        let mut inflight = self.group.inflight();
+        let current = inflight
+            .get(&self.key)
+            .is_some_and(|cell| Arc::ptr_eq(cell, self.cell));
+        // 表里一份，自己一份
+        if current && (self.cell.initialized() || Arc::strong_count(self.cell) == 2) {
+            inflight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn dedup() {
+        let group = Arc::new(SingleFlight::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..10 {
+            let group = Arc::clone(&group);
+            let calls = Arc::clone(&calls);
+            tasks.spawn(async move {
+                group
+                    .load("k", || async {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        // 让出几次，其余任务在此期间都会开始等待
+                        for _ in 0..10 {
+                            tokio::task::yield_now().await;
+                        }
+                        Ok::<_, ()>(42)
+                    })
+                    .await
+            });
+        }
+        while let Some(result) = tasks.join_next().await {
+            assert_eq!(result.unwrap(), Ok(42));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(group.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_leader() {
+        let group = SingleFlight::new();
+        tokio::select! {
+            biased;
+            _ = group.load(1, std::future::pending::<Result<i32, ()>>) => unreachable!(),
+            _ = tokio::task::yield_now() => {}
+        }
+        assert!(group.is_empty());
+        assert_eq!(group.load(1, || async { Ok::<_, ()>(7) }).await, Ok(7));
+        assert_eq!(group.load(2, || async { Err("down") }).await, Err("down"));
+        assert!(group.is_empty());
+    }
+}
+
+// 运行：RUSTFLAGS="--cfg loom" cargo test --release --lib --features async singleflight::loom_tests
+// OnceCell 内部用的是 std 的原语，loom 只探索这里的表锁和引用计数的交错
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Waker},
+    };
+
+    use loom::{future::block_on, thread};
+
+    use super::*;
+
+    // 一个调用拿到加载权之后被取消，另一个调用不管在它之前、之中还是之后到达，
+    // 都要能自己加载出结果，最后表里不留记录
+    #[test]
+    fn cancel_handoff() {
+        loom::model(|| {
+            let group = Arc::new(SingleFlight::new());
+            let handle = thread::spawn({
+                let group = Arc::clone(&group);
+                move || block_on(group.load(1, || async { Ok::<_, ()>(2) }))
+            });
+            {
+                let load = pin!(group.load(1, std::future::pending::<Result<_, ()>>));
+                let _ = load.poll(&mut Context::from_waker(Waker::noop()));
+            }
+            assert_eq!(handle.join().unwrap(), Ok(2));
+            assert!(group.is_empty());
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use crate::HashMap;
+
+// 不超过这个长度的字符串直接存在 entry 里；整个 SmallStr 是 24 字节，在 64 位目标上和 String 一样大
+const INLINE_CAP: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, buf: [u8; INLINE_CAP] },
+    Heap(Box<str>),
+}
+
+// 短字符串内联存储的 key：查找时少一次指针跳转，插入时不用为 key 单独分配内存。
+// 哈希和比较都和 str 一致，可以直接用 &str 查找
+#[derive(Clone)]
+pub struct SmallStr(Repr);
+
+impl SmallStr {
+    pub fn new(s: &str) -> Self {
+        if s.len() <= INLINE_CAP {
+            let mut buf = [0; INLINE_CAP];
+            buf[..s.len()].copy_from_slice(s.as_bytes());
+            SmallStr(Repr::Inline {
+                len: s.len() as u8,
+                buf,
+            })
+        } else {
+            SmallStr(Repr::Heap(s.into()))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            // SAFETY: buf 的前 len 个字节是从一个 &str 原样拷过来的，是合法的 UTF-8
+            Repr::Inline { len, buf } => unsafe {
+                std::str::from_utf8_unchecked(&buf[..*len as usize])
+            },
+            Repr::Heap(s) => s,
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+}
+
+impl Deref for SmallStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmallStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Hash for SmallStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialEq for SmallStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallStr {}
+
+impl PartialOrd for SmallStr {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallStr {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl From<&str> for SmallStr {
+    fn from(s: &str) -> Self {
+        SmallStr::new(s)
+    }
+}
+
+// 长字符串直接接管原来的分配
+impl From<String> for SmallStr {
+    fn from(s: String) -> Self {
+        if s.len() <= INLINE_CAP {
+            SmallStr::new(&s)
+        } else {
+            SmallStr(Repr::Heap(s.into_boxed_str()))
+        }
+    }
+}
+
+impl From<Box<str>> for SmallStr {
+    fn from(s: Box<str>) -> Self {
+        if s.len() <= INLINE_CAP {
+            SmallStr::new(&s)
+        } else {
+            SmallStr(Repr::Heap(s))
+        }
+    }
+}
+
+impl fmt::Debug for SmallStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SmallStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+pub type SmallStrMap<V> = HashMap<SmallStr, V>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_keys() {
+        assert!(std::mem::size_of::<SmallStr>() <= 24);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            std::mem::size_of::<SmallStr>(),
+            std::mem::size_of::<String>()
+        );
+
+        let mut map = SmallStrMap::new();
+        map.insert("user_id".into(), 1);
+        map.insert(SmallStr::from("a".repeat(INLINE_CAP)), 2);
+        map.insert(SmallStr::from("a".repeat(INLINE_CAP + 1)), 3);
+        assert_eq!(map.get("user_id"), Some(&1));
+        assert_eq!(map.get("a".repeat(INLINE_CAP).as_str()), Some(&2));
+        assert_eq!(map.get("a".repeat(INLINE_CAP + 1).as_str()), Some(&3));
+        assert_eq!(map.get("user"), None);
+
+        let inline: Vec<bool> = {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            keys.iter().map(|k| k.is_inline()).collect()
+        };
+        assert_eq!(inline, [true, false, true]);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    fs::{self, File},
+    hash::{BuildHasher, Hash},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::HashMap;
+
+// 文件格式：MAGIC，entry 个数（u64），然后每个 entry 依次是
+// key 的长度（u32）、key 的字节、value 的长度（u32）、value 的字节；整数都是小端序
+const MAGIC: &[u8; 4] = b"RHM1";
+
+// key 和 value 与字节串之间的转换；自定义类型实现这个 trait 就可以存进快照
+pub trait Codec: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+macro_rules! impl_codec_int {
+    ($($t:ty)*) => {$(
+        impl Codec for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(bytes: &[u8]) -> io::Result<Self> {
+                let bytes = bytes
+                    .try_into()
+                    .map_err(|_| invalid_data(concat!("wrong length for ", stringify!($t))))?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+impl_codec_int!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid_data("invalid utf-8"))
+    }
+}
+
+impl Codec for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(_bytes: &[u8]) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+impl Codec for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+pub(crate) fn write_field<T: Codec>(
+    writer: &mut impl Write,
+    value: &T,
+    buf: &mut Vec<u8>,
+) -> io::Result<()> {
+    buf.clear();
+    value.encode(buf);
+    let len = u32::try_from(buf.len()).map_err(|_| invalid_data("field longer than 4 GiB"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(buf)
+}
+
+pub(crate) fn read_field<T: Codec>(reader: &mut impl Read, buf: &mut Vec<u8>) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as u64;
+    // 按实际读到的字节增长，损坏的长度字段不会导致一次巨大的分配
+    buf.clear();
+    if reader.take(len).read_to_end(buf)? as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    T::decode(buf)
+}
+
+// 先写到同一目录下的临时文件再改名，写到一半崩溃不会破坏已有的文件
+pub(crate) fn write_atomically<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    write(&mut writer)?;
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    fs::rename(tmp, path)
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Codec,
+    V: Codec,
+{
+    pub fn save_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_atomically(path.as_ref(), |writer| {
+            writer.write_all(MAGIC)?;
+            writer.write_all(&(self.len() as u64).to_le_bytes())?;
+            let mut buf = Vec::new();
+            for (k, v) in self {
+                write_field(writer, k, &mut buf)?;
+                write_field(writer, v, &mut buf)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Codec + Hash + Eq,
+    V: Codec,
+    S: BuildHasher + Default,
+{
+    // 文件被截断或者内容不合法时返回 InvalidData / UnexpectedEof
+    pub fn load_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a map snapshot"));
+        }
+        let mut len = [0; 8];
+        reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len) as usize;
+
+        // 不相信文件里声明的长度，预分配设个上限
+        let mut map = HashMap::with_capacity_and_hasher(len.min(1 << 16), S::default());
+        let mut buf = Vec::new();
+        for _ in 0..len {
+            let k = read_field(&mut reader, &mut buf)?;
+            let v = read_field(&mut reader, &mut buf)?;
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("rust-hashmap-{}.snapshot", std::process::id()));
+        let map: HashMap<String, u64> = (0..1000).map(|i| (i.to_string(), i)).collect();
+        map.save_to(&path).unwrap();
+        let back: HashMap<String, u64> = HashMap::load_from(&path).unwrap();
+        assert_eq!(back.len(), 1000);
+        assert_eq!(back.get("42"), Some(&42));
+
+        // 截断的文件
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let err = HashMap::<String, u64>::load_from(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn zero_sized_values() {
+        let path = std::env::temp_dir().join(format!("rust-hashmap-{}.set", std::process::id()));
+        let set: HashMap<u32, ()> = (0..100).map(|i| (i, ())).collect();
+        set.save_to(&path).unwrap();
+        // 每个 entry 是 key 的长度、4 个字节的 key，() 只占一个为 0 的长度
+        assert_eq!(fs::metadata(&path).unwrap().len(), 4 + 8 + 100 * (8 + 4));
+        let back: HashMap<u32, ()> = HashMap::load_from(&path).unwrap();
+        assert_eq!(back.len(), 100);
+        assert!(back.contains_key(&99));
+
+        // 零大小的类型也照常走自己的 Codec
+        #[derive(Debug, PartialEq)]
+        struct Tag;
+        impl Codec for Tag {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.push(b't');
+            }
+
+            fn decode(bytes: &[u8]) -> io::Result<Self> {
+                match bytes {
+                    b"t" => Ok(Tag),
+                    _ => Err(invalid_data("bad tag")),
+                }
+            }
+        }
+        let tagged: HashMap<u32, Tag> = (0..10).map(|i| (i, Tag)).collect();
+        tagged.save_to(&path).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 4 + 8 + 10 * (8 + 5));
+        let back: HashMap<u32, Tag> = HashMap::load_from(&path).unwrap();
+        assert_eq!(back.get(&3), Some(&Tag));
+        fs::remove_file(&path).unwrap();
+    }
+}
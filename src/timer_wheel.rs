@@ -0,0 +1,115 @@
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+const BITS: u32 = 6;
+const SLOTS: usize = 1 << BITS;
+const MASK: u64 = SLOTS as u64 - 1;
+const LEVELS: usize = 4;
+
+// 分层时间轮：第 l 层每个槽覆盖 64^l 个 tick，高层的槽到期时把其中的条目下放（cascade）到低层
+pub(crate) struct TimerWheel<K> {
+    start: Instant,
+    resolution: Duration,
+    current: u64,
+    levels: Vec<Vec<Vec<(u64, K)>>>,
+}
+
+impl<K> TimerWheel<K> {
+    pub(crate) fn new(resolution: Duration) -> Self {
+        assert!(
+            !resolution.is_zero(),
+            "timer wheel resolution must be non-zero"
+        );
+        TimerWheel {
+            start: Instant::now(),
+            resolution,
+            current: 0,
+            levels: (0..LEVELS)
+                .map(|_| (0..SLOTS).map(|_| Vec::new()).collect())
+                .collect(),
+        }
+    }
+
+    // 向上取整，保证条目不会在 deadline 之前被触发
+    fn tick_of(&self, deadline: Instant) -> u64 {
+        let elapsed = deadline.saturating_duration_since(self.start).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        elapsed.div_ceil(resolution) as u64
+    }
+
+    pub(crate) fn schedule(&mut self, deadline: Instant, key: K) {
+        let tick = self.tick_of(deadline);
+        self.place(tick, key);
+    }
+
+    fn place(&mut self, tick: u64, key: K) {
+        // 已经到期的条目放到下一个 tick，当前槽已经触发过了
+        let tick = tick.max(self.current + 1);
+        let diff = tick ^ self.current;
+        let level = ((63 - diff.leading_zeros()) / BITS) as usize;
+        let level = level.min(LEVELS - 1);
+        let slot = ((tick >> (BITS * level as u32)) & MASK) as usize;
+        self.levels[level][slot].push((tick, key));
+    }
+
+    // 推进到 now，返回所有到期的 key
+    pub(crate) fn advance(&mut self, now: Instant) -> Vec<K> {
+        let target =
+            now.saturating_duration_since(self.start).as_nanos() / self.resolution.as_nanos();
+        let target = target as u64;
+        let mut fired = Vec::new();
+        while self.current < target {
+            self.current += 1;
+            for level in (1..LEVELS).rev() {
+                let shift = BITS * level as u32;
+                if self.current & ((1 << shift) - 1) != 0 {
+                    continue;
+                }
+                let slot = ((self.current >> shift) & MASK) as usize;
+                for (tick, key) in mem::take(&mut self.levels[level][slot]) {
+                    self.place_cascaded(tick, key, &mut fired);
+                }
+            }
+            let slot = (self.current & MASK) as usize;
+            fired.extend(
+                mem::take(&mut self.levels[0][slot])
+                    .into_iter()
+                    .map(|(_, key)| key),
+            );
+        }
+        fired
+    }
+
+    fn place_cascaded(&mut self, tick: u64, key: K, fired: &mut Vec<K>) {
+        if tick <= self.current {
+            fired.push(key);
+        } else {
+            self.place(tick, key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cascade() {
+        let mut wheel = TimerWheel::new(Duration::from_millis(1));
+        let start = wheel.start;
+        for ms in [1, 5, 63, 64, 65, 4095, 4096, 300_000] {
+            wheel.schedule(start + Duration::from_millis(ms), ms);
+        }
+        let mut fired = Vec::new();
+        for ms in [1, 5, 63, 64, 65, 4095, 4096, 300_000] {
+            assert!(wheel
+                .advance(start + Duration::from_millis(ms - 1))
+                .is_empty());
+            fired.extend(wheel.advance(start + Duration::from_millis(ms)));
+            assert_eq!(fired.last(), Some(&ms));
+        }
+        assert_eq!(fired.len(), 8);
+    }
+}
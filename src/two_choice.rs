@@ -0,0 +1,228 @@
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{DefaultHashBuilder, INITIAL_NBUCKETS};
+
+// 第二个哈希的种子，和 key 一起喂给同一个 BuildHasher
+const SECOND_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// 拉链法 + 两次选择：每个 key 用两个种子算出两个候选桶，插入时放进较短的那个，
+// 查找时两个桶都要看。n 个 key 时最长的链从 O(log n / log log n) 降到 O(log log n)，
+// 代价是每次操作多算一次哈希、多看一个桶
+pub struct TwoChoiceMap<K, V, S = DefaultHashBuilder> {
+    buckets: Vec<Vec<(K, V)>>,
+    items: usize,
+    hash_builder: S,
+}
+
+impl<K, V> TwoChoiceMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> TwoChoiceMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        TwoChoiceMap {
+            buckets: Vec::new(),
+            items: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.buckets.iter().flatten(),
+        }
+    }
+
+    // 最长的一条链，用来观察两次选择的效果
+    pub fn max_chain_len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+impl<K, V, S> Default for TwoChoiceMap<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> TwoChoiceMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn choices<Q>(&self, key: &Q, nbuckets: usize) -> (usize, usize)
+    where
+        Q: Hash + ?Sized,
+    {
+        let first = self.hash_builder.hash_one(key);
+        let second = self.hash_builder.hash_one((SECOND_SEED, key));
+        (
+            (first % nbuckets as u64) as usize,
+            (second % nbuckets as u64) as usize,
+        )
+    }
+
+    // 返回 (桶, 桶内下标)
+    fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let (first, second) = self.choices(key, self.buckets.len());
+        [first, second].into_iter().find_map(|bucket| {
+            self.buckets[bucket]
+                .iter()
+                .position(|(k, _)| k.borrow() == key)
+                .map(|idx| (bucket, idx))
+        })
+    }
+
+    fn push_shorter(&mut self, key: K, value: V) {
+        let (first, second) = self.choices(&key, self.buckets.len());
+        let bucket = if self.buckets[second].len() < self.buckets[first].len() {
+            second
+        } else {
+            first
+        };
+        self.buckets[bucket].push((key, value));
+    }
+
+    fn resize(&mut self) {
+        let target_size = match self.buckets.len() {
+            0 => INITIAL_NBUCKETS,
+            n => 2 * n,
+        };
+        let old = std::mem::replace(
+            &mut self.buckets,
+            (0..target_size).map(|_| Vec::new()).collect(),
+        );
+        for (key, value) in old.into_iter().flatten() {
+            self.push_shorter(key, value);
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some((bucket, idx)) = self.find(&key) {
+            return Some(std::mem::replace(&mut self.buckets[bucket][idx].1, value));
+        }
+        if self.buckets.is_empty() || self.items > 3 * self.buckets.len() / 4 {
+            self.resize();
+        }
+        self.push_shorter(key, value);
+        self.items += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, idx) = self.find(key)?;
+        Some(&self.buckets[bucket][idx].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, idx) = self.find(key)?;
+        Some(&mut self.buckets[bucket][idx].1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (bucket, idx) = self.find(key)?;
+        self.items -= 1;
+        Some(self.buckets[bucket].swap_remove(idx).1)
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for TwoChoiceMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = TwoChoiceMap::default();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::iter::Flatten<std::slice::Iter<'a, Vec<(K, V)>>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a TwoChoiceMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_chains() {
+        let mut map: TwoChoiceMap<_, _> = (0..100_000).map(|i| (i, i)).collect();
+        assert_eq!(map.len(), 100_000);
+        // 单一哈希在这个规模下最长的链一般是 6 左右，两次选择一般是 3
+        assert!(map.max_chain_len() <= 4, "{}", map.max_chain_len());
+        assert_eq!(map.insert(5, 50), Some(5));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.get(&6), Some(&7));
+        assert_eq!(map.remove(&5), Some(50));
+        assert_eq!(map.remove(&5), None);
+        assert!(!map.contains_key(&5));
+        assert_eq!(map.iter().count(), 99_999);
+    }
+}
@@ -0,0 +1,61 @@
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+
+use crate::HashMap;
+
+// 在 JS 里的用法和 Map 接近：key 只能是字符串，value 可以是任意 JS 值；
+// get 找不到时返回 undefined
+#[wasm_bindgen(js_name = HashMap)]
+pub struct JsHashMap {
+    map: HashMap<String, JsValue>,
+}
+
+#[wasm_bindgen(js_class = HashMap)]
+impl JsHashMap {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsHashMap {
+        JsHashMap {
+            map: HashMap::new(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn get(&self, key: &str) -> JsValue {
+        self.map.get(key).cloned().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn set(&mut self, key: String, value: JsValue) {
+        self.map.insert(key, value);
+    }
+
+    // 和 Map.prototype.delete 一样，返回 key 是否存在
+    pub fn delete(&mut self, key: &str) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.map = HashMap::new();
+    }
+
+    // [key, value] 组成的数组，可以直接传给 new Map(...)
+    pub fn entries(&self) -> Array {
+        self.map
+            .iter()
+            .map(|(k, v)| Array::of2(&JsValue::from_str(k), v))
+            .collect()
+    }
+}
+
+impl Default for JsHashMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
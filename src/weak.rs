@@ -0,0 +1,117 @@
+use std::{
+    borrow::Borrow,
+    hash::Hash,
+    sync::{Arc, Weak},
+};
+
+use crate::HashMap;
+
+// value 的生命周期由外部的 Arc 决定，Arc 全部释放后对应的 entry 视为不存在
+pub struct WeakValueMap<K, V> {
+    map: HashMap<K, Weak<V>>,
+    // 距离上次清理以来的插入次数，超过表的大小时清理一次，均摊 O(1)
+    inserts_since_gc: usize,
+}
+
+impl<K, V> WeakValueMap<K, V> {
+    pub fn new() -> Self {
+        WeakValueMap {
+            map: HashMap::new(),
+            inserts_since_gc: 0,
+        }
+    }
+
+    // 包含还没被清理掉的失效 entry
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // 删除所有失效的 entry，返回删除的个数
+    pub fn remove_expired(&mut self) -> usize {
+        let before = self.map.len();
+        self.map.retain(|_, value| value.strong_count() > 0);
+        self.inserts_since_gc = 0;
+        before - self.map.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, Arc<V>)> {
+        self.map
+            .iter()
+            .filter_map(|(key, value)| Some((key, value.upgrade()?)))
+    }
+}
+
+impl<K, V> Default for WeakValueMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> WeakValueMap<K, V>
+where
+    K: Hash + Eq,
+{
+    // 返回被覆盖的、仍然存活的旧值
+    pub fn insert(&mut self, key: K, value: &Arc<V>) -> Option<Arc<V>> {
+        self.inserts_since_gc += 1;
+        if self.inserts_since_gc > self.map.len() {
+            self.remove_expired();
+        }
+        self.map
+            .insert(key, Arc::downgrade(value))
+            .and_then(|old| old.upgrade())
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)?.upgrade()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map
+            .get(key)
+            .is_some_and(|value| value.strong_count() > 0)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.remove(key)?.upgrade()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weak() {
+        let mut map = WeakValueMap::new();
+        let foo = Arc::new(42);
+        let bar = Arc::new(7);
+        map.insert("foo", &foo);
+        map.insert("bar", &bar);
+        assert_eq!(map.get(&"foo").as_deref(), Some(&42));
+        drop(bar);
+        assert_eq!(map.get(&"bar"), None);
+        assert!(!map.contains_key(&"bar"));
+        assert_eq!(map.iter().count(), 1);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.remove_expired(), 1);
+        assert_eq!(map.remove(&"foo"), Some(foo));
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::LinkedHashMap;
+
+// 按总权重而不是条目数限制大小的 LRU 缓存：weigher 给每个 entry 一个权重，
+// 插入后从最久未访问的一端淘汰，直到总权重不超过 max_weight；单个 entry 超过上限时它自己也会被淘汰
+pub struct WeightedCache<K, V, F> {
+    // 每个 entry 的权重在插入时算好存下来，淘汰和删除时不需要再调用 weigher
+    entries: LinkedHashMap<K, (V, u32)>,
+    weigher: F,
+    weight: u64,
+    max_weight: u64,
+}
+
+impl<K, V, F> WeightedCache<K, V, F>
+where
+    F: Fn(&K, &V) -> u32,
+{
+    pub fn new(max_weight: u64, weigher: F) -> Self {
+        WeightedCache {
+            entries: LinkedHashMap::with_access_order(),
+            weigher,
+            weight: 0,
+            max_weight,
+        }
+    }
+}
+
+impl<K, V, F> WeightedCache<K, V, F> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 当前所有 entry 的权重之和
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    pub fn max_weight(&self) -> u64 {
+        self.max_weight
+    }
+
+    // 从最久未访问的 entry 开始
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, (v, _))| (k, v))
+    }
+}
+
+impl<K, V, F> WeightedCache<K, V, F>
+where
+    K: Hash + Eq,
+    F: Fn(&K, &V) -> u32,
+{
+    // 返回被覆盖的旧值；淘汰掉的 entry 直接丢弃
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let weight = (self.weigher)(&key, &value);
+        let old = self
+            .entries
+            .insert(key, (value, weight))
+            .map(|(old, old_weight)| {
+                self.weight -= u64::from(old_weight);
+                old
+            });
+        self.weight += u64::from(weight);
+        while self.weight > self.max_weight {
+            let (_, (_, evicted)) = self.entries.pop_front().unwrap();
+            self.weight -= u64::from(evicted);
+        }
+        old
+    }
+
+    // 会把 entry 标记为最近访问；只读查询用 peek
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|(v, _)| v)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.peek(key).map(|(v, _)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.contains_key(key)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, weight) = self.entries.remove(key)?;
+        self.weight -= u64::from(weight);
+        Some(value)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V, F> WeightedCache<K, V, F>
+where
+    K: Hash + Eq,
+    V: Clone,
+    F: Fn(&K, &V) -> u32,
+{
+    // 未命中时才调用 loader 并等待，成功的结果插入缓存，出错时缓存不变；
+    // 返回的是 value 的克隆，因为权重超过上限的值插入后马上就会被淘汰
+    pub async fn get_or_try_insert_with<L, Fut, E>(&mut self, key: K, loader: L) -> Result<V, E>
+    where
+        L: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value.clone());
+        }
+        let value = loader().await?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evict_by_weight() {
+        let mut cache = WeightedCache::new(10, |_: &&str, v: &Vec<u8>| v.len() as u32);
+        cache.insert("a", vec![0; 4]);
+        cache.insert("b", vec![0; 4]);
+        assert_eq!(cache.weight(), 8);
+        cache.get("a");
+        cache.insert("c", vec![0; 4]);
+        assert_eq!(cache.weight(), 8);
+        assert!(!cache.contains_key("b"));
+        assert!(cache.contains_key("a"));
+
+        assert_eq!(cache.insert("a", vec![0; 1]), Some(vec![0; 4]));
+        assert_eq!(cache.weight(), 5);
+        assert_eq!(cache.remove("c"), Some(vec![0; 4]));
+        assert_eq!(cache.weight(), 1);
+
+        cache.insert("huge", vec![0; 11]);
+        assert!(cache.is_empty());
+        assert_eq!(cache.weight(), 0);
+    }
+
+    #[cfg(all(feature = "async", not(loom)))]
+    #[tokio::test]
+    async fn load_on_miss() {
+        let mut cache = WeightedCache::new(10, |_: &&str, v: &Vec<u8>| v.len() as u32);
+        let mut calls = 0;
+        for _ in 0..3 {
+            let value = cache
+                .get_or_try_insert_with("a", || {
+                    calls += 1;
+                    async { Ok::<_, ()>(vec![1; 2]) }
+                })
+                .await;
+            assert_eq!(value, Ok(vec![1; 2]));
+        }
+        assert_eq!(calls, 1);
+
+        let err = cache
+            .get_or_try_insert_with("b", || async { Err("unavailable") })
+            .await;
+        assert_eq!(err, Err("unavailable"));
+        assert!(!cache.contains_key("b"));
+    }
+}